@@ -1,11 +1,13 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+use advent_of_code::parse::{lines, parse_all, signed, ParseError};
+use nom::{character::complete::space1, sequence::separated_pair};
 use std::collections::HashMap;
 
-advent_of_code::solution!(1);
+advent_of_code::solution!(1, show = show);
 
 #[must_use]
 pub fn part_one(input: &str) -> Option<u32> {
-    let (left, right) = parse_input(input);
+    let (left, right) = parse_input(input).ok()?;
     Some(
         left.into_iter()
             .zip(right)
@@ -16,7 +18,7 @@ pub fn part_one(input: &str) -> Option<u32> {
 
 #[must_use]
 pub fn part_two(input: &str) -> Option<u32> {
-    let (left, right) = parse_input(input);
+    let (left, right) = parse_input(input).ok()?;
     let mut occurrences = HashMap::new();
     for n in &left {
         occurrences.entry(*n).or_insert(0);
@@ -35,23 +37,24 @@ pub fn part_two(input: &str) -> Option<u32> {
     )
 }
 
-fn parse_input(input: &str) -> (Vec<i32>, Vec<i32>) {
-    let (mut left, mut right) = (Vec::new(), Vec::new());
-
-    for line in input.lines() {
-        let numbers: Vec<i32> = line
-            .split_whitespace()
-            .filter_map(|n| n.parse::<i32>().ok())
-            .collect();
-
-        left.push(numbers[0]);
-        right.push(numbers[1]);
-    }
+fn parse_input(input: &str) -> Result<(Vec<i32>, Vec<i32>), ParseError> {
+    let pairs: Vec<(i32, i32)> = parse_all(input, lines(separated_pair(signed, space1, signed)))?;
+    let (mut left, mut right): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
 
     left.sort_unstable();
     right.sort_unstable();
 
-    (left, right)
+    Ok((left, right))
+}
+
+/// Explains why the input could not be parsed, once after the last part.
+fn show(input: &str, part: u8) {
+    if !advent_of_code::template::is_last_part(part) {
+        return;
+    }
+    if let Err(e) = parse_input(input) {
+        eprintln!("{e}");
+    }
 }
 
 #[cfg(test)]
@@ -1,5 +1,7 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
-advent_of_code::solution!(2);
+advent_of_code::solution!(2, show = show);
+
+use advent_of_code::parse::{lines, parse_all, space_separated, unsigned, ParseError};
 
 #[derive(Debug, PartialEq)]
 enum LevelType {
@@ -9,7 +11,7 @@ enum LevelType {
 
 #[must_use]
 pub fn part_one(input: &str) -> Option<u32> {
-    let reports = parse_input(input);
+    let reports = parse_input(input).ok()?;
     let mut safe = 0;
     for report in reports {
         if is_report_safe(&report) {
@@ -33,7 +35,7 @@ fn is_report_safe(report: &[u32]) -> bool {
         let l1 = report[i];
         let l2 = report[i + 1];
 
-        let diff = if l1 > l2 { l1 - l2 } else { l2 - l1 };
+        let diff = l1.abs_diff(l2);
 
         if (level_type == Increasing && l1 > l2)
             || (level_type == Decresing && l1 < l2)
@@ -48,7 +50,7 @@ fn is_report_safe(report: &[u32]) -> bool {
 
 #[must_use]
 pub fn part_two(input: &str) -> Option<u32> {
-    let reports = parse_input(input);
+    let reports = parse_input(input).ok()?;
     let mut safe = 0;
     for report in reports {
         if is_report_safe(&report) {
@@ -69,18 +71,18 @@ pub fn part_two(input: &str) -> Option<u32> {
     Some(safe)
 }
 
-fn parse_input(input: &str) -> Vec<Vec<u32>> {
-    let mut reports = Vec::new();
+fn parse_input(input: &str) -> Result<Vec<Vec<u32>>, ParseError> {
+    parse_all(input, lines(space_separated(unsigned)))
+}
 
-    for line in input.lines() {
-        let levels: Vec<u32> = line
-            .split_whitespace()
-            .filter_map(|n| n.parse::<u32>().ok())
-            .collect();
-        reports.push(levels);
+/// Explains why the input could not be parsed, once after the last part.
+fn show(input: &str, part: u8) {
+    if !advent_of_code::template::is_last_part(part) {
+        return;
+    }
+    if let Err(e) = parse_input(input) {
+        eprintln!("{e}");
     }
-
-    reports
 }
 
 #[cfg(test)]
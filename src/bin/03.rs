@@ -1,97 +1,106 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
-advent_of_code::solution!(3);
-
-use nom::{
-    branch::alt,
-    bytes::complete::{tag, take_while_m_n},
-    character::complete::{anychar, char, multispace0},
-    combinator::{map_res, peek},
-    multi::{many1, many_till},
-    sequence::{delimited, preceded, tuple},
-    IResult,
-};
-
-#[derive(Debug, PartialEq)]
-enum Operation {
-    Mul { op1: u32, op2: u32 },
-    Do,
-    Dont,
-}
+advent_of_code::solution!(3, show = show);
+
+use advent_of_code::scan::{Instruction, Scanner};
+use std::collections::HashMap;
 
-fn digits(input: &str) -> IResult<&str, &str> {
-    take_while_m_n(1, 3, |c: char| c.is_ascii_digit())(input)
+/// The state the instructions work on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Machine {
+    enabled: bool,
+    sum: u32,
 }
 
-fn argument(input: &str) -> IResult<&str, u32> {
-    map_res(preceded(multispace0, digits), |digit_str: &str| {
-        digit_str.parse::<u32>()
-    })(input)
+impl Default for Machine {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sum: 0,
+        }
+    }
 }
 
-fn arguments(input: &str) -> IResult<&str, (u32, u32)> {
-    let (input, (arg1, _, arg2)) = tuple((argument, char(','), argument))(input)?;
-    Ok((input, (arg1, arg2)))
+/// Executes an instruction with its arguments, as many as it was registered with.
+type Handler = fn(&mut Machine, &[u32]);
+
+fn mul(machine: &mut Machine, args: &[u32]) {
+    if machine.enabled {
+        machine.sum += args[0] * args[1];
+    }
 }
 
-fn mul(input: &str) -> IResult<&str, Operation> {
-    let (input, _) = tag("mul")(input)?;
-    let (input, args) = delimited(char('('), arguments, char(')'))(input)?;
-    let op = Operation::Mul {
-        op1: args.0,
-        op2: args.1,
-    };
+const fn r#do(machine: &mut Machine, _: &[u32]) {
+    machine.enabled = true;
+}
 
-    Ok((input, op))
+const fn dont(machine: &mut Machine, _: &[u32]) {
+    machine.enabled = false;
 }
 
-fn r#do(input: &str) -> IResult<&str, Operation> {
-    let (input, _) = tag("do()")(input)?;
-    Ok((input, Operation::Do))
+/// Runs the instructions found in corrupted memory, from a table of instructions by name.
+#[derive(Default)]
+struct Interpreter {
+    scanner: Scanner,
+    handlers: HashMap<String, Handler>,
 }
 
-fn dont(input: &str) -> IResult<&str, Operation> {
-    let (input, _) = tag("don't()")(input)?;
-    Ok((input, Operation::Dont))
+impl Interpreter {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an instruction taking `arity` arguments.
+    fn register(mut self, name: &str, arity: usize, handler: Handler) -> Self {
+        self.scanner = self.scanner.instruction(name, arity);
+        self.handlers.insert(name.to_string(), handler);
+        self
+    }
+
+    /// Runs every instruction in `memory`. `step` sees each one before it runs, and whether the
+    /// machine was enabled at that point.
+    fn run(&self, memory: &str, mut step: impl FnMut(&Instruction, bool)) -> Machine {
+        let mut machine = Machine::default();
+        for instruction in self.scanner.scan(memory) {
+            step(&instruction, machine.enabled);
+            self.handlers[instruction.name](&mut machine, &instruction.args);
+        }
+        machine
+    }
 }
 
-fn operation(input: &str) -> IResult<&str, Operation> {
-    let (input, _) = many_till(anychar, peek(alt((mul, r#do, dont))))(input)?;
-    alt((mul, r#do, dont))(input)
+/// The interpreter for the instructions a part knows: only `mul` in part one.
+fn interpreter(part: u8) -> Interpreter {
+    let interpreter = Interpreter::new().register("mul", 2, mul);
+    if part == 1 {
+        return interpreter;
+    }
+    interpreter
+        .register("do", 0, r#do)
+        .register("don't", 0, dont)
 }
 
+#[must_use]
 pub fn part_one(input: &str) -> Option<u32> {
-    let (_, operations) = many1(operation)(input).ok()?;
-    Some(
-        operations
-            .into_iter()
-            .map(|op| {
-                if let Operation::Mul { op1, op2 } = op {
-                    op1 * op2
-                } else {
-                    0
-                }
-            })
-            .sum(),
-    )
+    Some(interpreter(1).run(input, |_, _| {}).sum)
 }
 
+#[must_use]
 pub fn part_two(input: &str) -> Option<u32> {
-    let (_, operations) = many1(operation)(input).ok()?;
-    let mut dont_flag = false;
-    let mut sum = 0;
-    for op in operations {
-        match op {
-            Operation::Dont => dont_flag = true,
-            Operation::Do => dont_flag = false,
-            Operation::Mul { op1, op2 } => {
-                if !dont_flag {
-                    sum += op1 * op2;
-                }
-            }
-        }
-    }
+    Some(interpreter(2).run(input, |_, _| {}).sum)
+}
 
-    Some(sum)
+/// Lists each instruction the part runs with `--trace`.
+fn show(input: &str, part: u8) {
+    if !std::env::args().any(|x| x == "--trace") {
+        return;
+    }
+    interpreter(part).run(input, |instruction, enabled| {
+        let state = if enabled { "enabled" } else { "disabled" };
+        println!(
+            "{:>6}  {:<12} {state}",
+            instruction.offset, instruction.text
+        );
+    });
 }
 
 #[cfg(test)]
@@ -111,4 +120,50 @@ mod tests {
         ));
         assert_eq!(result, Some(48));
     }
+
+    #[test]
+    fn test_trace() {
+        let interpreter = Interpreter::new()
+            .register("mul", 2, mul)
+            .register("do", 0, r#do)
+            .register("don't", 0, dont);
+        let mut trace = Vec::new();
+        let memory = advent_of_code::template::read_file_part("examples", DAY, 2);
+        interpreter.run(&memory, |instruction, enabled| {
+            trace.push((instruction.offset, instruction.text.to_string(), enabled));
+        });
+        assert_eq!(
+            trace,
+            [
+                (1, "mul(2,4)".to_string(), true),
+                (20, "don't()".to_string(), true),
+                (28, "mul(5,5)".to_string(), false),
+                (48, "mul(11,8)".to_string(), false),
+                (59, "do()".to_string(), false),
+                (64, "mul(8,5)".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_new_instruction() {
+        fn add(machine: &mut Machine, args: &[u32]) {
+            if machine.enabled {
+                machine.sum += args.iter().sum::<u32>();
+            }
+        }
+
+        let interpreter = Interpreter::new()
+            .register("mul", 2, mul)
+            .register("add", 3, add)
+            .register("don't", 0, dont);
+        let machine = interpreter.run("add(1,2,3)mul(2,3)xadd(4,5)don't()add(7,8,9)", |_, _| {});
+        assert_eq!(
+            machine,
+            Machine {
+                enabled: false,
+                sum: 12
+            }
+        );
+    }
 }
@@ -1,108 +1,104 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
-use std::collections::{HashMap, HashSet, VecDeque};
+use advent_of_code::graph::{Cycle, Graph};
+use advent_of_code::parse::{blank_line, comma_separated, lines, parse_all, unsigned, ParseError};
+use nom::{character::complete::char, sequence::separated_pair};
+use std::fmt;
 
-advent_of_code::solution!(5);
+advent_of_code::solution!(5, show = show);
+
+/// A page ordering rule `X|Y`: page X has to be printed before page Y.
+type Rule = (u32, u32);
+type Update = Vec<u32>;
+
+/// The middle page of an update.
+fn middle(update: &[u32]) -> u32 {
+    update[update.len() / 2]
+}
+
+/// An update that cannot be ordered because the rules between its pages form a cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Unorderable {
+    update: Update,
+    cycle: Cycle<u32>,
+}
+
+impl fmt::Display for Unorderable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "update {:?} cannot be ordered, its rules form a {}",
+            self.update, self.cycle
+        )
+    }
+}
+
+/// Each update together with its pages in the order of the rules.
+///
+/// Only the rules between an update's own pages apply to it. Pages no rule mentions can go
+/// anywhere, so sorting keeps them where they are relative to the others.
+fn sort_updates(
+    rules: Vec<Rule>,
+    updates: Vec<Update>,
+) -> Result<Vec<(Update, Update)>, Unorderable> {
+    let rules = Graph::from_iter(rules);
+    updates
+        .into_iter()
+        .map(|update| match rules.toposort_subset(&update) {
+            Ok(sorted) => Ok((update, sorted)),
+            Err(cycle) => Err(Unorderable { update, cycle }),
+        })
+        .collect()
+}
 
 #[must_use]
 pub fn part_one(input: &str) -> Option<u32> {
-    let (rules, pages) = parse_input(input);
-
-    let adjacency_list = get_adjacency_list(&rules);
-
-    let mut sum = 0;
-    'outer: for page in pages {
-        for (idx, n) in page.iter().enumerate() {
-            if !(idx + 1..page.len()).all(|idx| !adjacency_list[&page[idx]].contains(n)) {
-                continue 'outer;
-            }
-        }
-        sum += page[page.len() / 2];
-    }
+    let (rules, updates) = parse_input(input).ok()?;
+
+    // an update is in order if sorting it keeps it as it is
+    let sum = sort_updates(rules, updates)
+        .ok()?
+        .iter()
+        .filter(|(update, sorted)| update == sorted)
+        .map(|(update, _)| middle(update))
+        .sum();
 
     Some(sum)
 }
 
 #[must_use]
 pub fn part_two(input: &str) -> Option<u32> {
-    let (rules, pages) = parse_input(input);
-
-    let adjacency_list = get_adjacency_list(&rules);
-
-    let mut sum = 0;
-    'outer: for page in pages {
-        for (idx, n) in page.iter().enumerate() {
-            if !(idx + 1..page.len()).all(|idx| !adjacency_list[&page[idx]].contains(n)) {
-                // page is not in order, reorder it
-                let mut ordered_page = Vec::new();
-                let mut deque = page.iter().copied().collect::<VecDeque<_>>();
-                while !deque.is_empty() {
-                    if let Some(n) = deque.pop_front() {
-                        if deque
-                            .iter()
-                            .copied()
-                            .all(|r| !adjacency_list[&r].contains(&n))
-                        {
-                            ordered_page.push(n);
-                        } else {
-                            deque.push_back(n);
-                        }
-                    }
-                }
-                sum += ordered_page[ordered_page.len() / 2];
-                continue 'outer;
-            }
-        }
-    }
+    let (rules, updates) = parse_input(input).ok()?;
+
+    let sum = sort_updates(rules, updates)
+        .ok()?
+        .iter()
+        .filter(|(update, sorted)| update != sorted)
+        .map(|(_, sorted)| middle(sorted))
+        .sum();
 
     Some(sum)
 }
 
-fn get_adjacency_list(rules: &[(u32, u32)]) -> HashMap<u32, HashSet<u32>> {
-    let mut numbers = HashSet::new();
-    for &(k, v) in rules {
-        numbers.insert(k);
-        numbers.insert(v);
-    }
-
-    let mut adjacency_list: HashMap<u32, HashSet<u32>> = HashMap::new();
-    for num in numbers {
-        adjacency_list.insert(num, HashSet::new());
+/// Explains why both parts failed, once after the last part that runs: the input could not be
+/// parsed, or the rules leave an update without an order, and the cycle is named.
+fn show(input: &str, part: u8) {
+    if !advent_of_code::template::is_last_part(part) {
+        return;
     }
-    // Populate the adjacency list and in-degree counts
-    for &(before, after) in rules {
-        adjacency_list.entry(before).or_default().insert(after);
+    match parse_input(input).map(|(rules, updates)| sort_updates(rules, updates)) {
+        Err(e) => eprintln!("{e}"),
+        Ok(Err(unorderable)) => eprintln!("{unorderable}"),
+        Ok(Ok(_)) => {}
     }
-
-    adjacency_list
 }
 
-fn parse_input(input: &str) -> (Vec<(u32, u32)>, Vec<Vec<u32>>) {
-    let mut rules = Vec::new();
-    let mut pages = Vec::new();
-    let mut pages_flag = false;
-
-    for line in input.lines() {
-        if let Some((p1, p2)) = line.split_once('|') {
-            if let (Ok(pn1), Ok(pn2)) = (p1.parse::<u32>(), p2.parse::<u32>()) {
-                rules.push((pn1, pn2));
-            }
-            continue;
-        }
-        if line.is_empty() {
-            pages_flag = true;
-            continue;
-        }
-
-        if pages_flag {
-            let update = line
-                .split(',')
-                .filter_map(|p| p.parse::<u32>().ok())
-                .collect::<Vec<_>>();
-            pages.push(update);
-        }
-    }
-
-    (rules, pages)
+fn parse_input(input: &str) -> Result<(Vec<Rule>, Vec<Update>), ParseError> {
+    let rule = separated_pair(unsigned, char('|'), unsigned);
+    let update = comma_separated(unsigned);
+    parse_all(
+        input,
+        separated_pair(lines(rule), blank_line, lines(update)),
+    )
 }
 
 #[cfg(test)]
@@ -120,4 +116,29 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(123));
     }
+
+    #[test]
+    fn test_pages_without_rules() {
+        let input = "47|53\n97|47\n\n53,12,47,97\n12,97,47\n";
+        assert_eq!(part_one(input), Some(97));
+        assert_eq!(part_two(input), Some(47));
+    }
+
+    #[test]
+    fn test_cyclic_rules() {
+        // the cycle only matters for updates with all of its pages
+        let input = "1|2\n2|3\n3|1\n\n1,2,4\n2,1,4\n";
+        assert_eq!(part_one(input), Some(2));
+        assert_eq!(part_two(input), Some(2));
+
+        // an update with all of them fails both parts alike
+        let input = "1|2\n2|3\n3|1\n\n1,2,4\n3,2,1\n";
+        assert_eq!(part_one(input), None);
+        assert_eq!(part_two(input), None);
+        let (rules, updates) = parse_input(input).unwrap();
+        assert_eq!(
+            sort_updates(rules, updates).unwrap_err().to_string(),
+            "update [3, 2, 1] cannot be ordered, its rules form a cycle 1 -> 2 -> 3 -> 1"
+        );
+    }
 }
@@ -1,207 +1,275 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+use advent_of_code::geometry::{Direction, Point};
+use advent_of_code::grid::Grid;
+use advent_of_code::render::{render, Rgb, Tile};
 use std::collections::{HashMap, HashSet};
+use std::num::NonZero;
 
-advent_of_code::solution!(6);
+advent_of_code::solution!(6, show = show);
 
-#[repr(usize)]
-#[derive(Debug, PartialEq)]
-enum Guard {
-    Up,
-    Down,
-    Left,
-    Right,
-}
+/// The lab's obstructions, `true` where there is one.
+type Lab = Grid<bool>;
 
-const GUARD_SYM: [char; 4] = ['^', 'v', '<', '>'];
+/// Where the guard is and which way she faces.
+type State = (Point, Direction);
 
-#[must_use]
-pub fn part_one(input: &str) -> Option<u32> {
-    let mut lab = parse_input(input);
-    let start_pos = start_position(&lab);
-    let bounds_x = HashSet::from([0, lab.len() - 1]);
-    let bounds_y = HashSet::from([0, lab[0].len() - 1]);
-    let mut visited = HashSet::from([start_pos]);
-    let mut curr_pos = start_pos;
-    // includes start position
-    let mut steps = 1;
-    while !is_done(&bounds_x, &bounds_y, curr_pos) {
-        if can_move(&lab, curr_pos) {
-            curr_pos = advance_pos(&mut lab, curr_pos);
-            if !visited.contains(&curr_pos) {
-                steps += 1;
+fn parse_input(input: &str) -> Option<(Lab, Point)> {
+    let lab: Grid<char> = input.parse().ok()?;
+    let start = lab.find(|&tile| tile == '^').map(Point::from_index)?;
+    Some((lab.map(|&tile| tile == '#'), start))
+}
+
+/// The cells the guard visits after `start` until she leaves the lab, in the order she first
+/// steps onto them, each with the state she is in right before that first step.
+///
+/// Returns [`None`] if she never leaves the lab, also when she is walled in and only turns.
+fn patrol(lab: &Lab, start: Point) -> Option<Vec<(Point, State)>> {
+    // the directions the guard already left each cell in, as bits
+    let mut seen = Grid::new(lab.rows(), lab.cols(), 0u8);
+    let mut path = Vec::new();
+    let (mut pos, mut dir) = (start, Direction::Up);
+    // after 4 turns in place she faces the way she started turning from again
+    let mut turns = 0;
+    while let Some(&obstructed) = lab.at(pos + dir) {
+        if obstructed {
+            turns += 1;
+            if turns == 4 {
+                return None;
             }
-            visited.insert(curr_pos);
-        } else {
-            rotate90(&mut lab, curr_pos);
+            dir = dir.turn_right();
+            continue;
         }
+        turns = 0;
+        let bit = 1 << dir as u8;
+        if seen[pos] & bit != 0 {
+            return None;
+        }
+        seen[pos] |= bit;
+
+        let next = pos + dir;
+        if seen[next] == 0 {
+            path.push((next, (pos, dir)));
+        }
+        pos = next;
     }
 
-    Some(steps)
+    Some(path)
 }
 
-fn can_move(lab: &[Vec<char>], curr_pos: (usize, usize)) -> bool {
-    use Guard::{Down, Left, Right, Up};
-    let x = curr_pos.0;
-    let y = curr_pos.1;
-    let curr_sym = lab[x][y];
-    curr_sym == GUARD_SYM[Up as usize] && lab[x - 1][y] == '.'
-        || curr_sym == GUARD_SYM[Down as usize] && lab[x + 1][y] == '.'
-        || curr_sym == GUARD_SYM[Left as usize] && lab[x][y - 1] == '.'
-        || curr_sym == GUARD_SYM[Right as usize] && lab[x][y + 1] == '.'
+/// For every cell and direction, where the guard stops when walking that way: the last cell
+/// before the next obstruction, or [`None`] if she walks out of the lab.
+struct Jumps {
+    stops: [Grid<Option<Point>>; 4],
 }
 
-fn advance_pos(lab: &mut [Vec<char>], curr_pos: (usize, usize)) -> (usize, usize) {
-    use Guard::{Down, Left, Right, Up};
-    let mut curr_pos = curr_pos;
-    let x = curr_pos.0;
-    let y = curr_pos.1;
-    let curr_sym = lab[x][y];
-    if curr_sym == GUARD_SYM[Up as usize] {
-        lab[x][y] = '.';
-        curr_pos = (x - 1, y);
-        lab[x - 1][y] = curr_sym;
-    } else if curr_sym == GUARD_SYM[Down as usize] {
-        lab[x][y] = '.';
-        curr_pos = (x + 1, y);
-        lab[x + 1][y] = curr_sym;
-    } else if curr_sym == GUARD_SYM[Left as usize] {
-        lab[x][y] = '.';
-        curr_pos = (x, y - 1);
-        lab[x][y - 1] = curr_sym;
-    } else if curr_sym == GUARD_SYM[Right as usize] {
-        lab[x][y] = '.';
-        curr_pos = (x, y + 1);
-        lab[x][y + 1] = curr_sym;
+impl Jumps {
+    fn new(lab: &Lab) -> Self {
+        let positions = lab.positions().map(Point::from_index).collect::<Vec<_>>();
+        let stops = Direction::ALL.map(|dir| {
+            let mut stops = Grid::new(lab.rows(), lab.cols(), None);
+            // visit the cell ahead of each cell first.
+            let mut fill = |pos: Point| {
+                let ahead = pos + dir;
+                stops[pos] = match lab.at(ahead) {
+                    None => None,
+                    Some(true) => Some(pos),
+                    Some(false) => stops[ahead],
+                };
+            };
+            match dir {
+                Direction::Up | Direction::Left => positions.iter().copied().for_each(&mut fill),
+                Direction::Down | Direction::Right => {
+                    positions.iter().rev().copied().for_each(&mut fill);
+                }
+            }
+            stops
+        });
+
+        Self { stops }
     }
 
-    curr_pos
+    fn stop(&self, (pos, dir): State) -> Option<Point> {
+        self.stops[dir as usize][pos]
+    }
 }
 
-fn rotate90(lab: &mut [Vec<char>], curr_pos: (usize, usize)) {
-    use Guard::{Down, Left, Right, Up};
-    let x = curr_pos.0;
-    let y = curr_pos.1;
-    let curr_sym = lab[x][y];
-    if curr_sym == GUARD_SYM[Up as usize] {
-        lab[x][y] = GUARD_SYM[Right as usize];
-    } else if curr_sym == GUARD_SYM[Down as usize] {
-        lab[x][y] = GUARD_SYM[Left as usize];
-    } else if curr_sym == GUARD_SYM[Left as usize] {
-        lab[x][y] = GUARD_SYM[Up as usize];
-    } else if curr_sym == GUARD_SYM[Right as usize] {
-        lab[x][y] = GUARD_SYM[Down as usize];
-    }
+/// The number of steps from `from` in `dir` to reach `to`, if `to` lies ahead.
+fn steps_to(from: Point, dir: Direction, to: Point) -> Option<i64> {
+    let unit = dir.unit();
+    let delta = to - from;
+    let steps = delta.row * unit.row + delta.col * unit.col;
+    (steps >= 0 && unit * steps == delta).then_some(steps)
 }
 
-#[must_use] pub fn part_two(input: &str) -> Option<u32> {
-    use Guard::Up;
-    let mut lab = parse_input(input);
-    let start_pos = start_position(&lab);
-    let bounds_x = HashSet::from([0, lab.len() - 1]);
-    let bounds_y = HashSet::from([0, lab[0].len() - 1]);
-    let obstacle_positions = get_obstacle_positions(&lab);
-    // includes start position
-    let mut no_positions = 0;
-    'outer: for pos in obstacle_positions {
-        lab[pos.0][pos.1] = 'O';
-        let mut curr_pos = start_pos;
-        let mut visited = HashMap::from([((start_pos, GUARD_SYM[Up as usize]), false)]);
-        while !is_done(&bounds_x, &bounds_y, curr_pos) {
-            if can_move(&lab, curr_pos) {
-                curr_pos = advance_pos(&mut lab, curr_pos);
-                if visited.contains_key(&(curr_pos, lab[curr_pos.0][curr_pos.1])) {
-                    // check to see if th next position is also visited
-                    if is_next_pos_visited(&lab, &visited, curr_pos) {
-                        no_positions += 1;
-                        lab[pos.0][pos.1] = '.';
-                        lab[curr_pos.0][curr_pos.1] = '.';
-                        // reset to start position
-                        lab[start_pos.0][start_pos.1] = GUARD_SYM[Up as usize];
-                        continue 'outer;
-                    }
-                }
-                visited.insert((curr_pos, lab[curr_pos.0][curr_pos.1]), true);
-            } else {
-                rotate90(&mut lab, curr_pos);
-            }
+/// Whether the guard walks in a loop from `state` once `obstruction` is added to the lab.
+///
+/// She only ever changes direction at a turn, so she loops exactly if she turns in the same
+/// place and direction twice. `turns` is scratch space, passed in to reuse its allocation.
+fn loops(jumps: &Jumps, obstruction: Point, state: State, turns: &mut HashSet<State>) -> bool {
+    turns.clear();
+    let (mut pos, mut dir) = state;
+    loop {
+        let stop = jumps.stop((pos, dir));
+        // the new obstruction stops her early if it is between her and her usual stop.
+        let blocked = steps_to(pos, dir, obstruction).filter(|&steps| {
+            stop.is_none_or(|stop| steps_to(pos, dir, stop).is_some_and(|until| steps <= until))
+        });
+        pos = match (blocked, stop) {
+            (Some(steps), _) => pos + dir.unit() * (steps - 1),
+            (None, Some(stop)) => stop,
+            (None, None) => return false,
+        };
+        dir = dir.turn_right();
+        if !turns.insert((pos, dir)) {
+            return true;
         }
-        // loop not found, go to next position
-        lab[pos.0][pos.1] = '.';
-        lab[curr_pos.0][curr_pos.1] = '.';
-        // reset to start position
-        lab[start_pos.0][start_pos.1] = GUARD_SYM[Up as usize];
     }
+}
 
-    Some(no_positions)
+#[must_use]
+pub fn part_one(input: &str) -> Option<u32> {
+    let (lab, start) = parse_input(input)?;
+    let path = patrol(&lab, start)?;
+    // includes start position
+    u32::try_from(path.len() + 1).ok()
 }
 
-fn is_next_pos_visited(
-    lab: &[Vec<char>],
-    visited: &HashMap<((usize, usize), char), bool>,
-    curr_pos: (usize, usize),
-) -> bool {
-    use Guard::{Down, Left, Right, Up};
-    let x = curr_pos.0;
-    let y = curr_pos.1;
-
-    let curr_sym = lab[x][y];
-    if curr_sym == GUARD_SYM[Up as usize] {
-        visited.contains_key(&((x + 1, y), curr_sym))
-            || visited.contains_key(&((x, y - 1), curr_sym))
-            || visited.contains_key(&((x, y + 1), curr_sym))
-    } else if curr_sym == GUARD_SYM[Down as usize] {
-        visited.contains_key(&((x - 1, y), curr_sym))
-            || visited.contains_key(&((x, y - 1), curr_sym))
-            || visited.contains_key(&((x, y + 1), curr_sym))
-    } else if curr_sym == GUARD_SYM[Left as usize] {
-        visited.contains_key(&((x + 1, y), curr_sym))
-            || visited.contains_key(&((x - 1, y), curr_sym))
-            || visited.contains_key(&((x, y + 1), curr_sym))
-    } else if curr_sym == GUARD_SYM[Right as usize] {
-        visited.contains_key(&((x - 1, y), curr_sym))
-            || visited.contains_key(&((x + 1, y), curr_sym))
-            || visited.contains_key(&((x, y - 1), curr_sym))
-    } else {
-        false
-    }
+/// Every position where a new obstruction makes the guard loop, sorted, each with the state she
+/// is in right before she first runs into it.
+///
+/// An obstruction only changes anything on the guard's original path, and she walks that path
+/// unchanged until she first runs into it, so each trial starts right in front of it.
+fn obstructions(lab: &Lab, start: Point) -> Option<Vec<(Point, State)>> {
+    let path = {
+        advent_of_code::span!("patrol");
+        patrol(lab, start)?
+    };
+    let jumps = {
+        advent_of_code::span!("jumps");
+        Jumps::new(lab)
+    };
+
+    let threads = std::thread::available_parallelism().map_or(1, NonZero::get);
+    let chunk_size = path.len().div_ceil(threads).max(1);
+    let mut obstructions = std::thread::scope(|scope| {
+        path.chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(|| {
+                    advent_of_code::span!("loops");
+                    let mut turns = HashSet::new();
+                    chunk
+                        .iter()
+                        .filter(|&&(obstruction, state)| {
+                            loops(&jumps, obstruction, state, &mut turns)
+                        })
+                        .copied()
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect::<Vec<_>>()
+    });
+
+    obstructions.sort_unstable();
+    Some(obstructions)
 }
 
-fn get_obstacle_positions(lab: &[Vec<char>]) -> Vec<(usize, usize)> {
-    let mut positions = Vec::new();
-    for i in 0..lab.len() {
-        for j in 0..lab[0].len() {
-            if lab[i][j] == '.' {
-                positions.push((i, j));
-            }
+/// The states of the loop the guard walks from `state` with `obstruction` added to the lab, one
+/// per step or turn, or [`None`] if she leaves the lab.
+///
+/// Unlike [`loops`] this walks step by step, to find exactly where the loop closes: at the
+/// first state she is in for the second time.
+fn loop_path(lab: &Lab, obstruction: Point, state: State) -> Option<Vec<State>> {
+    let mut seen = HashMap::new();
+    let mut states = Vec::new();
+    let (mut pos, mut dir) = state;
+    while let Some(&obstructed) = lab.at(pos + dir) {
+        if let Some(&first) = seen.get(&(pos, dir)) {
+            return Some(states.split_off(first));
+        }
+        seen.insert((pos, dir), states.len());
+        states.push((pos, dir));
+
+        if obstructed || pos + dir == obstruction {
+            dir = dir.turn_right();
+        } else {
+            pos += dir;
         }
     }
 
-    positions
+    None
 }
 
-fn start_position(lab: &[Vec<char>]) -> (usize, usize) {
-    for i in 0..lab.len() {
-        for j in 0..lab[0].len() {
-            if lab[i][j] == GUARD_SYM[Guard::Up as usize] {
-                return (i, j);
-            }
-        }
+/// Lists the obstruction positions and draws the lab with all of them in yellow and the loop of
+/// the first one in green, its obstruction in red.
+fn report(lab: &Lab, start: Point, obstructions: &[(Point, State)]) {
+    println!("{} obstruction positions:", obstructions.len());
+    for (pos, _) in obstructions {
+        println!("  {pos}");
     }
 
-    (0, 0)
+    let Some(&(example, state)) = obstructions.first() else {
+        return;
+    };
+    // the loop's cells, drawn as `|`, `-` or `+` where it runs both ways.
+    let mut path = HashMap::new();
+    for (pos, dir) in loop_path(lab, example, state).unwrap_or_default() {
+        let symbol = if dir.is_horizontal() { '-' } else { '|' };
+        path.entry(pos)
+            .and_modify(|existing| {
+                if *existing != symbol {
+                    *existing = '+';
+                }
+            })
+            .or_insert(symbol);
+    }
+    let obstructions = obstructions
+        .iter()
+        .map(|&(pos, _)| pos)
+        .collect::<HashSet<_>>();
+
+    let frame = render(lab, |pos, &wall| {
+        let pos = Point::from_index(pos);
+        if wall {
+            Tile::new('#').fg(Rgb::GREY)
+        } else if pos == example {
+            Tile::new('O').fg(Rgb::RED)
+        } else if pos == start {
+            Tile::new('^').fg(Rgb::BLUE)
+        } else if let Some(&symbol) = path.get(&pos) {
+            Tile::new(symbol).fg(Rgb::GREEN)
+        } else if obstructions.contains(&pos) {
+            Tile::new('O').fg(Rgb::YELLOW)
+        } else {
+            Tile::new('.')
+        }
+    });
+    println!("loop with an obstruction at {example}:\n{frame}");
 }
 
-fn is_done(bounds_x: &HashSet<usize>, bounds_y: &HashSet<usize>, curr_pos: (usize, usize)) -> bool {
-    bounds_x.contains(&curr_pos.0) || bounds_y.contains(&curr_pos.1)
+#[must_use]
+pub fn part_two(input: &str) -> Option<u32> {
+    let (lab, start) = parse_input(input)?;
+    u32::try_from(obstructions(&lab, start)?.len()).ok()
 }
 
-fn parse_input(input: &str) -> Vec<Vec<char>> {
-    let mut lab = Vec::new();
-    for line in input.lines() {
-        lab.push(line.chars().collect::<Vec<_>>());
+/// Reports the obstructions after part two with `--report`.
+fn show(input: &str, part: u8) {
+    if part != 2 || !std::env::args().any(|x| x == "--report") {
+        return;
+    }
+    let Some((lab, start)) = parse_input(input) else {
+        return;
+    };
+    if let Some(obstructions) = obstructions(&lab, start) {
+        report(&lab, start, &obstructions);
     }
-
-    lab
 }
 
 #[cfg(test)]
@@ -219,4 +287,85 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(6));
     }
+
+    #[test]
+    fn test_obstructions() {
+        let (lab, start) =
+            parse_input(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let obstructions = obstructions(&lab, start).unwrap();
+        let positions = obstructions.iter().map(|&(pos, _)| pos).collect::<Vec<_>>();
+        assert_eq!(
+            positions,
+            [
+                Point::new(6, 3),
+                Point::new(7, 6),
+                Point::new(7, 7),
+                Point::new(8, 1),
+                Point::new(8, 3),
+                Point::new(9, 7),
+            ]
+        );
+
+        for (obstruction, state) in obstructions {
+            let path = loop_path(&lab, obstruction, state).unwrap();
+            // every state leads to the next one, and the last one back to the first
+            for (&(pos, dir), &next) in path.iter().zip(path.iter().cycle().skip(1)) {
+                let ahead = pos + dir;
+                if lab[ahead] || ahead == obstruction {
+                    assert_eq!(next, (pos, dir.turn_right()));
+                } else {
+                    assert_eq!(next, (ahead, dir));
+                }
+            }
+            assert_eq!(
+                path.iter().collect::<HashSet<_>>().len(),
+                path.len(),
+                "a loop passes each state once"
+            );
+        }
+    }
+
+    #[test]
+    fn test_loop_of_turns() {
+        // boxed in by the new obstruction, the guard only turns in place
+        let lab = "...\n#^#\n.#.";
+        assert_eq!(part_two(lab), Some(1));
+        let (lab, start) = parse_input(lab).unwrap();
+        assert_eq!(
+            loop_path(&lab, Point::new(0, 1), (start, Direction::Up))
+                .unwrap()
+                .len(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_walled_in() {
+        let lab = ".#.\n#^#\n.#.";
+        assert_eq!(part_one(lab), None);
+        assert_eq!(part_two(lab), None);
+
+        // the obstruction search turns in place until a turn repeats as well
+        let (lab, start) = parse_input(lab).unwrap();
+        assert!(loops(
+            &Jumps::new(&lab),
+            Point::new(0, 0),
+            (start, Direction::Up),
+            &mut HashSet::new()
+        ));
+    }
+
+    #[test]
+    fn test_jumps() {
+        let (lab, start) =
+            parse_input(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let jumps = Jumps::new(&lab);
+        assert_eq!(jumps.stop((start, Direction::Up)), Some(Point::new(1, 4)));
+        assert_eq!(jumps.stop((start, Direction::Left)), Some(Point::new(6, 2)));
+        assert_eq!(jumps.stop((start, Direction::Right)), None);
+        assert_eq!(
+            jumps.stop((Point::new(1, 8), Direction::Right)),
+            Some(Point::new(1, 8))
+        );
+    }
 }
@@ -1,96 +1,270 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
 
-use itertools::Itertools;
+use advent_of_code::parse::{key_values, lines, parse_all, unsigned, ParseError};
+use std::fmt::Write;
 
-advent_of_code::solution!(7);
+advent_of_code::solution!(7, show = show);
 
-#[derive(Debug, PartialEq)]
-enum Operation {
-    Add,
-    Multiply,
-    Concatenation,
+type Equation = (u64, Vec<u64>);
+
+/// A binary operator of an equation, evaluated left to right.
+///
+/// Equations are solved backwards from the target, so an operator also has to undo itself:
+/// given the result and the right operand, find the left operands that produce the result.
+/// Rejecting impossible results there is what prunes the search.
+trait Operator {
+    /// How `--explain` writes the operator.
+    fn symbol(&self) -> &'static str;
+
+    /// `lhs op rhs`, or [`None`] if that is undefined or overflows.
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64>;
+
+    /// The `lhs` for which `lhs op rhs == result`, or [`None`] if there is none.
+    fn undo(&self, result: u64, rhs: u64) -> Option<Undo>;
 }
 
-#[must_use]
-pub fn part_one(input: &str) -> Option<u64> {
-    use Operation::{Add, Multiply};
-    let equations = parse_input(input);
-    let operations = [Add, Multiply];
-    Some(solve_equation(&equations, &operations))
+/// The left operands an operator can be undone to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Undo {
+    /// Only this one.
+    Lhs(u64),
+    /// Every one, like for a product with a factor of 0.
+    Any,
 }
 
-#[must_use]
-pub fn part_two(input: &str) -> Option<u64> {
-    use Operation::{Add, Concatenation, Multiply};
-    let equations = parse_input(input);
-    let operations = [Add, Multiply, Concatenation];
-    Some(solve_equation(&equations, &operations))
-}
-
-fn solve_equation(equations: &[(u64, Vec<u64>)], operations: &[Operation]) -> u64 {
-    use Operation::{Add, Concatenation, Multiply};
-    let mut total = 0u64;
-    for equation in equations {
-        let combinations = std::iter::repeat(operations.iter())
-            .take(equation.1.len() - 1)
-            .multi_cartesian_product()
-            .collect::<Vec<_>>();
-
-        for combination in combinations {
-            let mut result = equation.1[0];
-            for (idx, n) in equation.1[1..].iter().enumerate() {
-                match combination.get(idx) {
-                    Some(Add) => {
-                        result += n;
-                    }
-                    Some(Multiply) => {
-                        result *= n;
-                    }
-                    Some(Concatenation) => {
-                        let mut temp = *n;
-                        let mut multiplier = 1;
-                        while temp > 0 {
-                            multiplier *= 10;
-                            temp /= 10;
-                        }
-                        result = result * multiplier + *n;
-                    }
-                    _ => {
-                        panic!("Unknown operation");
-                    }
-                }
-            }
-            if result == equation.0 {
-                total += result;
-                break;
+struct Add;
+
+impl Operator for Add {
+    fn symbol(&self) -> &'static str {
+        "+"
+    }
+
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+        lhs.checked_add(rhs)
+    }
+
+    fn undo(&self, result: u64, rhs: u64) -> Option<Undo> {
+        result.checked_sub(rhs).map(Undo::Lhs)
+    }
+}
+
+struct Multiply;
+
+impl Operator for Multiply {
+    fn symbol(&self) -> &'static str {
+        "*"
+    }
+
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+        lhs.checked_mul(rhs)
+    }
+
+    /// Only divisible results can be undone. A factor of 0 makes 0 out of any `lhs`.
+    fn undo(&self, result: u64, rhs: u64) -> Option<Undo> {
+        match rhs {
+            0 => (result == 0).then_some(Undo::Any),
+            _ => result.is_multiple_of(rhs).then(|| Undo::Lhs(result / rhs)),
+        }
+    }
+}
+
+/// Appends the digits of `rhs` to `lhs`.
+struct Concatenate;
+
+impl Concatenate {
+    /// The power of 10 that shifts a number left by the digits of `rhs`.
+    fn shift(rhs: u64) -> Option<u64> {
+        10u64.checked_pow(rhs.checked_ilog10().unwrap_or(0) + 1)
+    }
+}
+
+impl Operator for Concatenate {
+    fn symbol(&self) -> &'static str {
+        "||"
+    }
+
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+        lhs.checked_mul(Self::shift(rhs)?)?.checked_add(rhs)
+    }
+
+    /// Only results ending in the digits of `rhs` can be undone.
+    fn undo(&self, result: u64, rhs: u64) -> Option<Undo> {
+        let shift = Self::shift(rhs)?;
+        (result % shift == rhs).then(|| Undo::Lhs(result / shift))
+    }
+}
+
+const PART_ONE: [&dyn Operator; 2] = [&Add, &Multiply];
+const PART_TWO: [&dyn Operator; 3] = [&Add, &Multiply, &Concatenate];
+
+/// The operators that make `numbers` evaluate to `target`, or [`None`] if there are none.
+///
+/// Works backwards from the last number: each operator that can produce the target from it
+/// leaves a smaller equation over the other numbers, with the undone target as its target. If
+/// any target will do, the other numbers only need to evaluate at all.
+fn solve<'a>(
+    target: u64,
+    numbers: &[u64],
+    operators: &[&'a dyn Operator],
+) -> Option<Vec<&'a dyn Operator>> {
+    let (&last, rest) = numbers.split_last()?;
+    if rest.is_empty() {
+        return (last == target).then(Vec::new);
+    }
+    operators.iter().find_map(|&operator| {
+        let mut solution = match operator.undo(target, last)? {
+            Undo::Lhs(lhs) => solve(lhs, rest, operators)?,
+            Undo::Any => defined(rest, operators)?,
+        };
+        solution.push(operator);
+        Some(solution)
+    })
+}
+
+/// Operators for which `numbers` evaluate to some value, or [`None`] if every combination is
+/// undefined or overflows.
+fn defined<'a>(numbers: &[u64], operators: &[&'a dyn Operator]) -> Option<Vec<&'a dyn Operator>> {
+    fn extend<'a>(
+        lhs: u64,
+        numbers: &[u64],
+        operators: &[&'a dyn Operator],
+        solution: &mut Vec<&'a dyn Operator>,
+    ) -> bool {
+        let Some((&rhs, rest)) = numbers.split_first() else {
+            return true;
+        };
+        operators.iter().any(|&operator| {
+            let Some(value) = operator.apply(lhs, rhs) else {
+                return false;
+            };
+            solution.push(operator);
+            if extend(value, rest, operators, solution) {
+                return true;
             }
+            solution.pop();
+            false
+        })
+    }
+
+    let (&first, rest) = numbers.split_first()?;
+    let mut solution = Vec::new();
+    extend(first, rest, operators, &mut solution).then_some(solution)
+}
+
+/// Evaluates the equation left to right.
+fn evaluate(numbers: &[u64], operators: &[&dyn Operator]) -> Option<u64> {
+    operators
+        .iter()
+        .zip(&numbers[1..])
+        .try_fold(numbers[0], |lhs, (operator, &rhs)| operator.apply(lhs, rhs))
+}
+
+/// Writes a solved equation as e.g. `292 = 11 + 6 * 16 + 20`.
+fn explain(target: u64, numbers: &[u64], operators: &[&dyn Operator]) -> String {
+    let mut explanation = format!("{target} = {}", numbers[0]);
+    for (operator, n) in operators.iter().zip(&numbers[1..]) {
+        let _ = write!(explanation, " {} {n}", operator.symbol());
+    }
+    explanation
+}
+
+/// The sum of the targets of all equations the operators can solve.
+fn calibrate(equations: &[Equation], operators: &[&dyn Operator]) -> u64 {
+    let mut total = 0;
+    for (target, numbers) in equations {
+        if let Some(solution) = solve(*target, numbers, operators) {
+            debug_assert_eq!(evaluate(numbers, &solution), Some(*target));
+            total += target;
         }
     }
 
     total
 }
 
-fn parse_input(input: &str) -> Vec<(u64, Vec<u64>)> {
-    let mut equations = Vec::new();
-    for line in input.lines() {
-        if let Some((result, calibrations)) = line.split_once(':') {
-            let calibrations: Vec<u64> = calibrations
-                .split_whitespace()
-                .filter_map(|n| n.parse::<u64>().ok())
-                .collect();
-            if let Ok(result) = result.parse::<u64>() {
-                equations.push((result, calibrations));
+#[must_use]
+pub fn part_one(input: &str) -> Option<u64> {
+    let equations = parse_input(input).ok()?;
+    Some(calibrate(&equations, &PART_ONE))
+}
+
+#[must_use]
+pub fn part_two(input: &str) -> Option<u64> {
+    let equations = parse_input(input).ok()?;
+    Some(calibrate(&equations, &PART_TWO))
+}
+
+/// Prints how each equation is solved with the operators of the part with `--explain`. Explains
+/// why the input could not be parsed once after the last part.
+fn show(input: &str, part: u8) {
+    let equations = match parse_input(input) {
+        Ok(equations) => equations,
+        Err(e) => {
+            if advent_of_code::template::is_last_part(part) {
+                eprintln!("{e}");
             }
+            return;
+        }
+    };
+    if !std::env::args().any(|x| x == "--explain") {
+        return;
+    }
+    let operators: &[&dyn Operator] = if part == 1 { &PART_ONE } else { &PART_TWO };
+    for (target, numbers) in equations {
+        match solve(target, &numbers, operators) {
+            Some(solution) => println!("{}", explain(target, &numbers, &solution)),
+            None => println!("{target} cannot be made from {numbers:?}"),
         }
     }
+}
 
-    equations
+fn parse_input(input: &str) -> Result<Vec<Equation>, ParseError> {
+    parse_all(input, lines(key_values(unsigned, unsigned)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    struct Subtract;
+
+    impl Operator for Subtract {
+        fn symbol(&self) -> &'static str {
+            "-"
+        }
+
+        fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+            lhs.checked_sub(rhs)
+        }
+
+        fn undo(&self, result: u64, rhs: u64) -> Option<Undo> {
+            result.checked_add(rhs).map(Undo::Lhs)
+        }
+    }
+
+    struct Power;
+
+    impl Operator for Power {
+        fn symbol(&self) -> &'static str {
+            "^"
+        }
+
+        fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+            lhs.checked_pow(u32::try_from(rhs).ok()?)
+        }
+
+        /// The exact integer root, if any. Any base to the power of 0 is 1.
+        fn undo(&self, result: u64, rhs: u64) -> Option<Undo> {
+            let exponent = u32::try_from(rhs).ok()?;
+            if exponent == 0 {
+                return (result == 1).then_some(Undo::Any);
+            }
+            let root = (0..=result)
+                .take_while(|base| base.checked_pow(exponent).is_some_and(|p| p <= result))
+                .last()?;
+            (root.pow(exponent) == result).then_some(Undo::Lhs(root))
+        }
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
@@ -102,4 +276,64 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(11387));
     }
+
+    #[test]
+    fn test_explain() {
+        let numbers = [81, 40, 27];
+        let solution = solve(3267, &numbers, &PART_ONE).unwrap();
+        // 81 + 40 * 27 works too, the search tries the last operator first
+        assert_eq!(explain(3267, &numbers, &solution), "3267 = 81 * 40 + 27");
+
+        let numbers = [6, 8, 6, 15];
+        let solution = solve(7290, &numbers, &PART_TWO).unwrap();
+        assert_eq!(explain(7290, &numbers, &solution), "7290 = 6 * 8 || 6 * 15");
+        assert!(solve(7290, &numbers, &PART_ONE).is_none());
+
+        let numbers = [7, 0, 2];
+        let solution = solve(2, &numbers, &PART_ONE).unwrap();
+        assert_eq!(explain(2, &numbers, &solution), "2 = 7 * 0 + 2");
+        assert_eq!(part_one("0: 7 0\n15: 7 0 2\n"), Some(0));
+        assert_eq!(part_one("2: 7 0 2\n0: 3 5 0\n"), Some(2));
+    }
+
+    #[test]
+    fn test_solutions_evaluate_to_target() {
+        let equations = parse_input(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        for (target, numbers) in equations {
+            if let Some(solution) = solve(target, &numbers, &PART_TWO) {
+                assert_eq!(evaluate(&numbers, &solution), Some(target));
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_operators() {
+        let operators: [&dyn Operator; 4] = [&Add, &Subtract, &Multiply, &Power];
+        let numbers = [5, 3, 3, 2];
+        let solution = solve(64, &numbers, &operators).unwrap();
+        assert_eq!(explain(64, &numbers, &solution), "64 = 5 - 3 ^ 3 ^ 2");
+
+        // the backwards search finds exactly the targets evaluating every combination reaches,
+        // also where a 0 or an exponent of 0 lets any left operand through
+        for numbers in [[5, 3, 3, 2], [7, 0, 3, 2], [2, 3, 0, 4], [6, 2, 4, 0]] {
+            let mut reachable = vec![numbers[0]];
+            for &n in &numbers[1..] {
+                reachable = reachable
+                    .iter()
+                    .flat_map(|&lhs| operators.iter().filter_map(move |op| op.apply(lhs, n)))
+                    .collect();
+            }
+            for target in 0..1_000 {
+                let solution = solve(target, &numbers, &operators);
+                assert_eq!(
+                    solution.is_some(),
+                    reachable.contains(&target),
+                    "{target} from {numbers:?}"
+                );
+                if let Some(solution) = solution {
+                    assert_eq!(evaluate(&numbers, &solution), Some(target));
+                }
+            }
+        }
+    }
 }
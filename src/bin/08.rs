@@ -1,11 +1,13 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+use advent_of_code::geometry::Point;
+use advent_of_code::grid::Grid;
 use std::collections::{HashMap, HashSet};
 
 advent_of_code::solution!(8);
 
 #[must_use]
 pub fn part_one(input: &str) -> Option<usize> {
-    let map = parse_input(input);
+    let map: Grid<char> = input.parse().ok()?;
     let mut count = 0;
     let mut seen = HashSet::new();
     let antennas = get_antennas(&map);
@@ -18,94 +20,47 @@ pub fn part_one(input: &str) -> Option<usize> {
     Some(count)
 }
 fn update_antinodes(
-    antenna_pair: [(usize, usize); 2],
-    seen: &mut HashSet<(usize, usize)>,
-    map: &[Vec<char>],
+    antenna_pair: [Point; 2],
+    seen: &mut HashSet<Point>,
+    map: &Grid<char>,
     count: &mut usize,
     multi_antinodes_flg: bool,
-) -> Option<()> {
-    let [(x1, y1), (x2, y2)] = antenna_pair;
-    let dx = if x1 > x2 { x1 - x2 } else { x2 - x1 };
-    let dy = if y1 > y2 { y1 - y2 } else { y2 - y1 };
-    let (x1, y1, x2, y2, dx, dy) = (
-        isize::try_from(x1).ok()?,
-        isize::try_from(y1).ok()?,
-        isize::try_from(x2).ok()?,
-        isize::try_from(y2).ok()?,
-        isize::try_from(dx).ok()?,
-        isize::try_from(dy).ok()?,
-    );
-    let mut positions = [
-        (x1 - dx, y1 + dy), // top-right
-        (x1 - dx, y1 - dy), // top-right
-        (x2 + dx, y2 + dy), // bottom-right
-        (x2 + dx, y2 - dy), // bottom-left
+) {
+    let [a1, a2] = antenna_pair;
+    let dx = (a1.row - a2.row).abs();
+    let dy = (a1.col - a2.col).abs();
+    let steps = [
+        Point::new(-dx, dy),  // top-right
+        Point::new(-dx, -dy), // top-left
+        Point::new(dx, dy),   // bottom-right
+        Point::new(dx, -dy),  // bottom-left
     ];
+    let mut positions = [a1 + steps[0], a1 + steps[1], a2 + steps[2], a2 + steps[3]];
     loop {
-        for (idx, (ax, ay)) in positions.iter_mut().enumerate() {
-            if is_pos_out_of_bounds(map, (*ax, *ay)).is_some() {
-                continue;
-            }
-            if are_3_points_inline((x1, y1), (x2, y2), (*ax, *ay))
-                && !seen.contains(&(usize::try_from(*ax).ok()?, usize::try_from(*ay).ok()?))
-            {
-                if multi_antinodes_flg {
-                    if map[usize::try_from(*ax).ok()?][usize::try_from(*ay).ok()?] == '.' {
+        for (pos, &step) in positions.iter_mut().zip(&steps) {
+            if let Some(&tile) = map.at(*pos) {
+                if are_3_points_inline(a1, a2, *pos) && !seen.contains(pos) {
+                    if multi_antinodes_flg {
+                        if tile == '.' {
+                            *count += 1;
+                            seen.insert(*pos);
+                        }
+                    } else {
                         *count += 1;
-                        seen.insert((usize::try_from(*ax).ok()?, usize::try_from(*ay).ok()?));
+                        seen.insert(*pos);
                     }
-                } else {
-                    *count += 1;
-                    seen.insert((usize::try_from(*ax).ok()?, usize::try_from(*ay).ok()?));
-                }
-            }
-            match idx {
-                0 => {
-                    *ax -= dx;
-                    *ay += dy;
-                }
-                1 => {
-                    *ax -= dx;
-                    *ay -= dy;
                 }
-                2 => {
-                    *ax += dx;
-                    *ay += dy;
-                }
-                3 => {
-                    *ax += dx;
-                    *ay -= dy;
-                }
-                _ => unreachable!(),
             }
+            *pos += step;
         }
 
-        if !multi_antinodes_flg
-            || positions
-                .iter()
-                .all(|pos| is_pos_out_of_bounds(map, *pos).is_some())
-        {
+        if !multi_antinodes_flg || positions.iter().all(|&pos| map.at(pos).is_none()) {
             break;
         }
     }
-
-    Some(())
 }
 
-fn is_pos_out_of_bounds(map: &[Vec<char>], pos: (isize, isize)) -> Option<bool> {
-    let (ax, ay) = pos;
-    let flag = ax < 0
-        || ax >= isize::try_from(map.len()).ok()?
-        || ay < 0
-        || ay >= isize::try_from(map[0].len()).ok()?;
-    if flag {
-        Some(flag)
-    } else {
-        None
-    }
-}
-
-fn get_all_antennas_combinations(positions: &[(usize, usize)]) -> Vec<[(usize, usize); 2]> {
+fn get_all_antennas_combinations(positions: &[Point]) -> Vec<[Point; 2]> {
     let mut combinations = Vec::new();
     for i in 0..positions.len() {
         for j in i + 1..positions.len() {
@@ -116,24 +71,21 @@ fn get_all_antennas_combinations(positions: &[(usize, usize)]) -> Vec<[(usize, u
     combinations
 }
 
-const fn are_3_points_inline(a1: (isize, isize), a2: (isize, isize), in1: (isize, isize)) -> bool {
-    let (x1, y1) = a1;
-    let (x2, y2) = a2;
-    let (x3, y3) = in1;
+const fn are_3_points_inline(a1: Point, a2: Point, in1: Point) -> bool {
+    let (x1, y1) = (a1.row, a1.col);
+    let (x2, y2) = (a2.row, a2.col);
+    let (x3, y3) = (in1.row, in1.col);
     (x1 * (y2 - y3) + x2 * (y3 - y1) + x3 * (y1 - y2)).abs() / 2 == 0
 }
 
-fn get_antennas(map: &[Vec<char>]) -> HashMap<char, Vec<(usize, usize)>> {
+fn get_antennas(map: &Grid<char>) -> HashMap<char, Vec<Point>> {
     let mut antennas = HashMap::new();
-    for i in 0..map.len() {
-        for j in 0..map[0].len() {
-            let sym = map[i][j];
-            if sym.is_numeric() || sym.is_lowercase() || sym.is_uppercase() {
-                antennas
-                    .entry(sym)
-                    .and_modify(|pos: &mut Vec<(usize, usize)>| pos.push((i, j)))
-                    .or_insert_with(|| Vec::from([(i, j)]));
-            }
+    for (pos, &sym) in map.iter() {
+        if sym.is_numeric() || sym.is_lowercase() || sym.is_uppercase() {
+            antennas
+                .entry(sym)
+                .and_modify(|positions: &mut Vec<Point>| positions.push(Point::from_index(pos)))
+                .or_insert_with(|| Vec::from([Point::from_index(pos)]));
         }
     }
 
@@ -142,7 +94,7 @@ fn get_antennas(map: &[Vec<char>]) -> HashMap<char, Vec<(usize, usize)>> {
 
 #[must_use]
 pub fn part_two(input: &str) -> Option<usize> {
-    let map = parse_input(input);
+    let map: Grid<char> = input.parse().ok()?;
 
     let mut count = 0;
     let mut seen = HashSet::new();
@@ -162,16 +114,6 @@ pub fn part_two(input: &str) -> Option<usize> {
     )
 }
 
-fn parse_input(input: &str) -> Vec<Vec<char>> {
-    let mut map = Vec::new();
-    for line in input.lines() {
-        let chars: Vec<char> = line.chars().collect();
-        map.push(chars);
-    }
-
-    map
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,145 +1,247 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
 
-advent_of_code::solution!(9);
+advent_of_code::solution!(9, show = show);
 
-#[must_use]
-pub fn part_one(input: &str) -> Option<u64> {
-    let disk_map = input
-        .trim_end()
-        .chars()
-        .filter_map(|c| c.to_digit(10).map(|c| i32::try_from(c).ok()))
-        .flatten()
-        .collect::<Vec<_>>();
-    let mut blocks = get_blocks_from_disk_map(&disk_map)?;
-    Some(compute_checksum(&mut blocks))
+/// A run of consecutive blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Span {
+    start: usize,
+    len: usize,
 }
 
-fn get_blocks_from_disk_map(disk_map: &[i32]) -> Option<Vec<i32>> {
-    let mut blocks = Vec::new();
-    let mut i = 0;
-    for (idx, d) in disk_map.iter().enumerate() {
-        let times = usize::try_from(*d).ok()?;
-        if idx % 2 != 0 {
-            blocks.extend(std::iter::repeat_n(-1, times));
-        } else {
-            blocks.extend(std::iter::repeat_n(i, times));
-            i += 1;
-        }
+impl Span {
+    const fn end(self) -> usize {
+        self.start + self.len
     }
+}
 
-    Some(blocks)
+/// The blocks of a disk, as the spans each file occupies. Every block outside them is free.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Disk {
+    /// By file id, the spans of the file in the order of its blocks.
+    files: Vec<Vec<Span>>,
+    /// The number of blocks on the disk.
+    size: usize,
 }
 
-fn compute_checksum(blocks: &mut [i32]) -> u64 {
-    // get all the digits positions
-    let mut digits_pos = Vec::new();
-    for (idx, c) in blocks.iter().enumerate() {
-        if *c != -1 {
-            digits_pos.push(idx);
+/// How scattered the files and the free space on a disk are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fragmentation {
+    used_blocks: usize,
+    free_blocks: usize,
+    /// The number of spans the files occupy.
+    file_spans: usize,
+    /// The number of files split over more than one span.
+    fragmented_files: usize,
+    /// The number of runs of free blocks, including one at the end of the disk.
+    free_spans: usize,
+    largest_free_span: usize,
+}
+
+impl Disk {
+    /// Reads the disk map, alternating between the lengths of files and of free space.
+    fn parse(input: &str) -> Self {
+        let mut files = Vec::new();
+        let mut size = 0;
+        let digits = input.trim_end().chars().filter_map(|c| c.to_digit(10));
+        for (idx, len) in digits.enumerate() {
+            let span = Span {
+                start: size,
+                len: len as usize,
+            };
+            if idx % 2 == 0 {
+                files.push(if span.len > 0 { vec![span] } else { Vec::new() });
+            }
+            size = span.end();
         }
+
+        Self { files, size }
     }
-    let mut all_positions = blocks
-        .iter()
-        .enumerate()
-        .map(|(idx, _)| idx)
-        .collect::<Vec<_>>();
-    let no_free_spaces = blocks.len() - digits_pos.len();
-    let mut moved_spaces = blocks.iter().rev().take_while(|&&c| c == -1).count();
-
-    for idx in 0..blocks.len() {
-        if moved_spaces == no_free_spaces {
-            break;
+
+    /// The runs of free blocks, from the start of the disk.
+    fn free_spans(&self) -> Vec<Span> {
+        let mut used = self.files.iter().flatten().copied().collect::<Vec<_>>();
+        used.sort_unstable_by_key(|span| span.start);
+        let mut free = Vec::new();
+        let mut start = 0;
+        for span in used.into_iter().chain([Span {
+            start: self.size,
+            len: 0,
+        }]) {
+            if span.start > start {
+                free.push(Span {
+                    start,
+                    len: span.start - start,
+                });
+            }
+            start = span.end();
         }
-        if blocks[idx] == -1 {
-            loop {
-                let pos = all_positions.pop().unwrap_or(0);
-                if blocks[pos] == -1 {
-                    moved_spaces += 1;
-                    continue;
+        free
+    }
+
+    /// Moves single blocks from the end of the disk into the first free block, until the free
+    /// space is all at the end. Files get split up wherever the free space is.
+    fn compact_blocks(&mut self) {
+        let mut free = self.free_spans();
+        let mut next_free = 0;
+        for file in self.files.iter_mut().rev() {
+            let mut moved = Vec::new();
+            while let Some(span) = file.last_mut() {
+                let Some(gap) = free.get_mut(next_free).filter(|gap| gap.start < span.start) else {
+                    break;
+                };
+                // the last blocks of the file fill the gap from its start
+                let len = span.len.min(gap.len);
+                moved.push(Span {
+                    start: gap.start,
+                    len,
+                });
+                span.len -= len;
+                gap.start += len;
+                gap.len -= len;
+                if span.len == 0 {
+                    file.pop();
+                }
+                if gap.len == 0 {
+                    next_free += 1;
                 }
-                blocks.swap(idx, pos);
-                moved_spaces += 1;
-                break;
             }
+            file.extend(moved);
+            file.sort_unstable_by_key(|span| span.start);
+            // a part that lands right before the rest of the file joins it again
+            file.dedup_by(|next, span| {
+                let adjacent = span.end() == next.start;
+                if adjacent {
+                    span.len += next.len;
+                }
+                adjacent
+            });
         }
     }
-    blocks
-        .iter()
-        .enumerate()
-        .filter(|&(_, &d)| d >= 0)
-        .map(|(idx, &d)| idx as u64 * u64::try_from(d).unwrap_or(0))
-        .sum()
-}
 
-fn compute_checksum_whole_file(blocks: &mut [i32]) -> u64 {
-    let file_ids_list = blocks
-        .iter()
-        .enumerate()
-        .collect::<Vec<_>>()
-        .as_slice()
-        .chunk_by(|a, b| a.1 == b.1)
-        .filter(|chunk| *chunk[0].1 != -1)
-        .map(|chunk| {
-            chunk.iter()
-                .map(|&(idx,& _)| idx)
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>();
-
-    let mut free_space_list = blocks
-        .iter()
-        .enumerate()
-        .collect::<Vec<_>>()
-        .as_slice()
-        .chunk_by(|a, b| a.1 == b.1)
-        .filter(|chunk| *chunk[0].1 == -1)
-        .map(|chunk| {
-            chunk.iter()
-                .map(|&(idx,& _)| idx)
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>();
-
-    for (idx_file, id_list) in file_ids_list.iter().enumerate().rev() {
-        let mut compacted: Option<(usize, usize)> = None;
-        for (idx_free, free_list) in free_space_list.iter_mut().enumerate() {
-            if !free_list.is_empty() && free_list[0] > id_list[0] {
-                break;
-            }
-            if id_list.len() <= free_list.len() {
-                // enough room to move whole file
-                for (&if1, &if2) in id_list.iter().zip(free_list.iter()) {
-                    blocks.swap(if1, if2);
+    /// Moves every file once, from the highest id down, to the leftmost free span it fits in if
+    /// that is left of the file. Files already split over several spans stay where they are.
+    ///
+    /// The free spans are kept in one heap per length, ordered by start, so the leftmost free
+    /// span a file fits in is the leftmost of the heap tops from the file's length up. Space a
+    /// file leaves behind is never used: the files after it are all further left.
+    fn compact_files(&mut self) {
+        let free = self.free_spans();
+        let longest = free.iter().map(|span| span.len).max().unwrap_or(0);
+        let mut heaps = vec![BinaryHeap::new(); longest + 1];
+        for span in free {
+            heaps[span.len].push(Reverse(span.start));
+        }
+
+        for file in self.files.iter_mut().rev() {
+            let [span] = file.as_mut_slice() else {
+                continue;
+            };
+            let leftmost = (span.len..=longest)
+                .filter_map(|len| heaps[len].peek().map(|&Reverse(start)| (start, len)))
+                .filter(|&(start, _)| start < span.start)
+                .min();
+            if let Some((start, len)) = leftmost {
+                heaps[len].pop();
+                if len > span.len {
+                    heaps[len - span.len].push(Reverse(start + span.len));
                 }
-                compacted = Some((idx_file, idx_free));
-                break;
+                span.start = start;
             }
         }
-        if let Some((idx_list, idx_free)) = compacted {
-            // if file blocks have been compacted, shring the freespace available
-            free_space_list[idx_free] =
-                free_space_list[idx_free][file_ids_list[idx_list].len()..].to_vec();
+    }
+
+    fn checksum(&self) -> u64 {
+        let mut checksum = 0;
+        for (id, span) in self
+            .files
+            .iter()
+            .enumerate()
+            .flat_map(|(id, spans)| spans.iter().map(move |span| (id, span)))
+        {
+            // the sum of the block positions of the span
+            let positions = span.len * span.start + span.len * span.len.saturating_sub(1) / 2;
+            checksum += (id * positions) as u64;
+        }
+        checksum
+    }
+
+    fn fragmentation(&self) -> Fragmentation {
+        let free = self.free_spans();
+        let used_blocks = self.files.iter().flatten().map(|span| span.len).sum();
+        Fragmentation {
+            used_blocks,
+            free_blocks: self.size - used_blocks,
+            file_spans: self.files.iter().map(Vec::len).sum(),
+            fragmented_files: self.files.iter().filter(|spans| spans.len() > 1).count(),
+            free_spans: free.len(),
+            largest_free_span: free.iter().map(|span| span.len).max().unwrap_or(0),
         }
     }
-    blocks
-        .iter()
-        .enumerate()
-        .filter(|&(_, &d)| d >= 0)
-        .map(|(idx, &d)| idx as u64 * u64::try_from(d).unwrap_or(0))
-        .sum()
+}
+
+/// Draws the blocks like the puzzle, e.g. `00...111...2`: each block of a file as its id, each
+/// free block as `.`. Ids from 10 up only show their last digit.
+impl fmt::Display for Disk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut blocks = vec!['.'; self.size];
+        for (id, spans) in self.files.iter().enumerate() {
+            let digit = u32::try_from(id % 10)
+                .ok()
+                .and_then(|d| char::from_digit(d, 10))
+                .unwrap_or('?');
+            for span in spans {
+                blocks[span.start..span.end()].fill(digit);
+            }
+        }
+        write!(f, "{}", blocks.into_iter().collect::<String>())
+    }
+}
+
+impl fmt::Display for Fragmentation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} used and {} free blocks, files in {} spans ({} files fragmented), {} free spans, the largest of {} blocks",
+            self.used_blocks,
+            self.free_blocks,
+            self.file_spans,
+            self.fragmented_files,
+            self.free_spans,
+            self.largest_free_span
+        )
+    }
+}
+
+#[must_use]
+pub fn part_one(input: &str) -> Option<u64> {
+    let mut disk = Disk::parse(input);
+    disk.compact_blocks();
+    Some(disk.checksum())
 }
 
 #[must_use]
 pub fn part_two(input: &str) -> Option<u64> {
-    let disk_map = input
-        .trim_end()
-        .chars()
-        .filter_map(|c| c.to_digit(10).map(|c| i32::try_from(c).ok()))
-        .flatten()
-        .collect::<Vec<_>>();
-    let mut blocks = get_blocks_from_disk_map(&disk_map)?;
-
-    Some(compute_checksum_whole_file(&mut blocks))
+    let mut disk = Disk::parse(input);
+    disk.compact_files();
+    Some(disk.checksum())
+}
+
+/// Prints the layout the part compacted the disk to and how fragmented it is with `--display`.
+fn show(input: &str, part: u8) {
+    if !std::env::args().any(|x| x == "--display") {
+        return;
+    }
+    let mut disk = Disk::parse(input);
+    if part == 1 {
+        disk.compact_blocks();
+    } else {
+        disk.compact_files();
+    }
+    println!("{disk}\n{}", disk.fragmentation());
 }
 
 #[cfg(test)]
@@ -157,4 +259,68 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(2858));
     }
+
+    #[test]
+    fn test_display() {
+        let mut disk = Disk::parse("12345");
+        assert_eq!(disk.to_string(), "0..111....22222");
+        disk.compact_blocks();
+        assert_eq!(disk.to_string(), "022111222......");
+
+        let disk = Disk::parse(&advent_of_code::template::read_file("examples", DAY));
+        assert_eq!(
+            disk.to_string(),
+            "00...111...2...333.44.5555.6666.777.888899"
+        );
+        let mut blocks = disk.clone();
+        blocks.compact_blocks();
+        assert_eq!(
+            blocks.to_string(),
+            "0099811188827773336446555566.............."
+        );
+        let mut files = disk;
+        files.compact_files();
+        assert_eq!(
+            files.to_string(),
+            "00992111777.44.333....5555.6666.....8888.."
+        );
+    }
+
+    #[test]
+    fn test_fragmentation() {
+        let mut disk = Disk::parse(&advent_of_code::template::read_file("examples", DAY));
+        disk.compact_blocks();
+        assert_eq!(
+            disk.fragmentation(),
+            Fragmentation {
+                used_blocks: 28,
+                free_blocks: 14,
+                file_spans: 13,
+                fragmented_files: 2,
+                free_spans: 1,
+                largest_free_span: 14,
+            }
+        );
+
+        let mut disk = Disk::parse(&advent_of_code::template::read_file("examples", DAY));
+        disk.compact_files();
+        let fragmentation = disk.fragmentation();
+        assert_eq!(fragmentation.fragmented_files, 0);
+        assert_eq!(fragmentation.free_spans, 6);
+        assert_eq!(fragmentation.largest_free_span, 5);
+    }
+
+    #[test]
+    fn test_checksum_matches_blocks() {
+        let disk = Disk::parse("2333133121414131402");
+        let mut blocks = disk;
+        blocks.compact_files();
+        let checksum = blocks
+            .to_string()
+            .chars()
+            .enumerate()
+            .filter_map(|(pos, c)| c.to_digit(10).map(|id| pos as u64 * u64::from(id)))
+            .sum::<u64>();
+        assert_eq!(blocks.checksum(), checksum);
+    }
 }
@@ -1,125 +1,184 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
-advent_of_code::solution!(10);
-
-struct TopographicMap(Vec<Vec<i32>>, Vec<(usize, usize)>, Vec<(usize, usize)>);
-
-fn parse_input(input: &str) -> TopographicMap {
-    let mut map = Vec::new();
-    let mut trail_heads = Vec::new();
-    let mut trail_ends = Vec::new();
-    for (idx, r) in input.lines().enumerate() {
-        map.push(
-            r.chars()
-                .enumerate()
-                .filter_map(|(cidx, c)| {
-                    if c == '0' {
-                        trail_heads.push((idx, cidx));
-                    } else if c == '9' {
-                        trail_ends.push((idx, cidx));
-                    }
-                    if c == '.' {
-                        Some(-1)
-                    } else {
-                        char::to_digit(c, 10).and_then(|d| i32::try_from(d).ok())
-                    }
-                })
-                .collect(),
-        );
-    }
+use advent_of_code::grid::Grid;
+use advent_of_code::render::{render, Rgb, Tile};
+use std::collections::HashSet;
+
+advent_of_code::solution!(10, show = show);
 
-    TopographicMap(map, trail_heads, trail_ends)
+/// Heights from 0 to 9, `.` marks impassable tiles.
+type TopographicMap = Grid<Option<u8>>;
+
+const TRAIL_HEAD: u8 = 0;
+const TRAIL_END: u8 = 9;
+
+fn parse_input(input: &str) -> Option<TopographicMap> {
+    TopographicMap::parse_with(input, |c| match c {
+        '.' => Some(None),
+        _ => c.to_digit(10).and_then(|d| u8::try_from(d).ok()).map(Some),
+    })
+    .ok()
 }
 
-fn find_paths(
-    map: &[Vec<i32>],
-    trail_head: (usize, usize),
-    trail_end: (usize, usize),
-    path: &mut Vec<(usize, usize)>,
-    visited: &mut Vec<Vec<bool>>,
-    paths: &mut Vec<Vec<(usize, usize)>>,
-) {
-    // If current position is the end point, save the path
-    if trail_head == trail_end {
-        paths.push(path.clone());
-        return;
+/// The positions one step uphill from `pos`.
+fn uphill(map: &TopographicMap, pos: (usize, usize)) -> Vec<(usize, usize)> {
+    let height = map[pos].map(|h| h + 1);
+    map.neighbours4(pos)
+        .filter(|&next| height.is_some() && map[next] == height)
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TrailHead {
+    pos: (usize, usize),
+    /// The number of height 9 positions reachable from the trailhead.
+    score: usize,
+    /// The number of distinct trails from the trailhead to any height 9.
+    rating: usize,
+}
+
+/// Scores and rates every trailhead in one pass over the heights, from the summits down: the
+/// trails from a position are the trails from its uphill neighbours, one step longer.
+///
+/// Ratings add up, scores take the union of the summits the neighbours reach. Only the summit
+/// sets of two adjacent heights are kept at a time.
+fn trail_heads(map: &TopographicMap) -> Vec<TrailHead> {
+    let mut levels = vec![Vec::new(); usize::from(TRAIL_END) + 1];
+    for (pos, &height) in map.iter() {
+        if let Some(height) = height {
+            levels[usize::from(height)].push(pos);
+        }
     }
 
-    // Directions: right, down, left, up
-    let directions = vec![(0, 1), (1, 0), (0, -1), (-1, 0)];
+    // summits are numbered in the order of the top level, a bit each.
+    let words = levels[usize::from(TRAIL_END)].len().div_ceil(64);
+    let mut summits = Grid::new(map.rows(), map.cols(), Vec::new());
+    let mut ratings = Grid::new(map.rows(), map.cols(), 0);
+    for (idx, &pos) in levels[usize::from(TRAIL_END)].iter().enumerate() {
+        summits[pos] = vec![0u64; words];
+        summits[pos][idx / 64] |= 1 << (idx % 64);
+        ratings[pos] = 1;
+    }
 
-    for (dx, dy) in directions {
-        let new_x = isize::try_from(trail_head.0).unwrap_or(0) + dx;
-        let new_y = isize::try_from(trail_head.1).unwrap_or(0) + dy;
-        if new_x < 0 || new_y < 0 {
-            continue;
+    for height in (usize::from(TRAIL_HEAD)..usize::from(TRAIL_END)).rev() {
+        for &pos in &levels[height] {
+            let mut reached = vec![0u64; words];
+            for next in uphill(map, pos) {
+                ratings[pos] += ratings[next];
+                for (word, &other) in reached.iter_mut().zip(&summits[next]) {
+                    *word |= other;
+                }
+            }
+            summits[pos] = reached;
+        }
+        for &pos in &levels[height + 1] {
+            summits[pos] = Vec::new();
         }
+    }
 
-        let (new_x, new_y) = (
-            usize::try_from(new_x).unwrap_or(0),
-            usize::try_from(new_y).unwrap_or(0),
-        );
+    levels[usize::from(TRAIL_HEAD)]
+        .iter()
+        .map(|&pos| TrailHead {
+            pos,
+            score: summits[pos]
+                .iter()
+                .map(|word| word.count_ones() as usize)
+                .sum(),
+            rating: ratings[pos],
+        })
+        .collect()
+}
 
-        if new_x < map.len()
-            && new_y < map[0].len()
-            && !visited[new_x][new_y]
-            && map[new_x][new_y] != -1
-            && (map[new_x][new_y] - map[trail_head.0][trail_head.1]).abs() == 1
-            && map[new_x][new_y] > map[trail_head.0][trail_head.1]
-        {
-            // Mark as visited and add to path
-            visited[new_x][new_y] = true;
-            path.push((new_x, new_y));
-
-            // Recurse
-            find_paths(map, (new_x, new_y), trail_end, path, visited, paths);
-
-            // Backtrack
-            path.pop();
-            visited[new_x][new_y] = false;
+/// Enumerates the trails from a position up to height 9 depth-first, one at a time, so a caller
+/// can inspect a few trails without finding all of them.
+struct Trails<'a> {
+    map: &'a TopographicMap,
+    /// The trail so far.
+    path: Vec<(usize, usize)>,
+    /// For each position on the trail, the uphill neighbours not tried yet.
+    untried: Vec<Vec<(usize, usize)>>,
+}
+
+impl<'a> Trails<'a> {
+    fn new(map: &'a TopographicMap, start: (usize, usize)) -> Self {
+        Self {
+            map,
+            path: vec![start],
+            untried: vec![Self::next_steps(map, start)],
         }
     }
+
+    /// The uphill neighbours, reversed so that popping tries them in order.
+    fn next_steps(map: &TopographicMap, pos: (usize, usize)) -> Vec<(usize, usize)> {
+        let mut steps = uphill(map, pos);
+        steps.reverse();
+        steps
+    }
 }
 
-fn find_all_trails(
-    map: &[Vec<i32>],
-    trail_head: &[(usize, usize)],
-    trail_end: &[(usize, usize)],
-) -> Vec<Vec<Vec<(usize, usize)>>> {
-    let mut all_trails = Vec::new();
-    for &s in trail_head {
-        for &e in trail_end {
-            let mut visited = vec![vec![false; map[0].len()]; map.len()];
-            let mut path = vec![s];
-            visited[s.0][s.1] = true;
-            let mut results = Vec::new();
-            find_paths(map, s, e, &mut path, &mut visited, &mut results);
-            all_trails.push(results);
+impl Iterator for Trails<'_> {
+    /// The positions of a trail, from the start to the summit.
+    type Item = Vec<(usize, usize)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(next) = self.untried.last_mut()?.pop() else {
+                self.untried.pop();
+                self.path.pop();
+                continue;
+            };
+            self.path.push(next);
+            if self.map[next] == Some(TRAIL_END) {
+                let trail = self.path.clone();
+                self.path.pop();
+                return Some(trail);
+            }
+            self.untried.push(Self::next_steps(self.map, next));
         }
     }
-    all_trails
+}
+
+/// Prints the map with the trails of the highest rated trailhead highlighted.
+fn display(map: &TopographicMap, heads: &[TrailHead]) {
+    let Some(head) = heads.iter().max_by_key(|head| head.rating) else {
+        return;
+    };
+    let on_trail = Trails::new(map, head.pos).flatten().collect::<HashSet<_>>();
+    let frame = render(map, |pos, &height| {
+        let symbol = height.map_or('.', |h| char::from(b'0' + h));
+        if pos == head.pos {
+            Tile::new(symbol).fg(Rgb::YELLOW)
+        } else if on_trail.contains(&pos) {
+            Tile::new(symbol).fg(Rgb::GREEN)
+        } else {
+            Tile::new(symbol).fg(Rgb::GREY)
+        }
+    });
+    println!(
+        "{frame}\ntrailhead {:?}: score {}, rating {}",
+        head.pos, head.score, head.rating
+    );
 }
 
 #[must_use]
 pub fn part_one(input: &str) -> Option<usize> {
-    let TopographicMap(map, trail_head, trail_end) = parse_input(input);
-    Some(
-        find_all_trails(&map, &trail_head, &trail_end)
-            .into_iter()
-            .filter(|p| !p.is_empty())
-            .count(),
-    )
+    let map = parse_input(input)?;
+    Some(trail_heads(&map).iter().map(|head| head.score).sum())
 }
 
 #[must_use]
 pub fn part_two(input: &str) -> Option<usize> {
-    let TopographicMap(map, trail_head, trail_end) = parse_input(input);
-    Some(
-        find_all_trails(&map, &trail_head, &trail_end)
-            .into_iter()
-            .filter(|p| !p.is_empty())
-            .map(|p| p.len())
-            .sum(),
-    )
+    let map = parse_input(input)?;
+    Some(trail_heads(&map).iter().map(|head| head.rating).sum())
+}
+
+/// Displays the best trailhead after part two with `--display`.
+fn show(input: &str, part: u8) {
+    if part != 2 || !std::env::args().any(|x| x == "--display") {
+        return;
+    }
+    if let Some(map) = parse_input(input) {
+        display(&map, &trail_heads(&map));
+    }
 }
 
 #[cfg(test)]
@@ -137,4 +196,61 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(81));
     }
+
+    #[test]
+    fn test_impassable_tiles() {
+        let input = ".....0.\n..4321.\n..5..2.\n..6543.\n..7..4.\n..8765.\n..9....";
+        assert_eq!(part_one(input), Some(1));
+        assert_eq!(part_two(input), Some(3));
+    }
+
+    #[test]
+    fn test_trails() {
+        let map = parse_input(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        for head in trail_heads(&map) {
+            let trails = Trails::new(&map, head.pos).collect::<Vec<_>>();
+            assert_eq!(trails.len(), head.rating);
+            let summits = trails
+                .iter()
+                .map(|trail| trail[trail.len() - 1])
+                .collect::<HashSet<_>>();
+            assert_eq!(summits.len(), head.score);
+            for trail in trails {
+                assert_eq!(trail.len(), 10);
+                assert_eq!(trail[0], head.pos);
+            }
+        }
+
+        let input = ".....0.\n..4321.\n..5..2.\n..6543.\n..7..4.\n..8765.\n..9....";
+        let map = parse_input(input).unwrap();
+        // neighbours are tried up, down, left, right
+        let first = Trails::new(&map, (0, 5)).next().unwrap();
+        assert_eq!(
+            first,
+            [
+                (0, 5),
+                (1, 5),
+                (2, 5),
+                (3, 5),
+                (4, 5),
+                (5, 5),
+                (5, 4),
+                (5, 3),
+                (5, 2),
+                (6, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_many_summits() {
+        // straight trails up 128 columns, so the summits take two words of bits
+        let mut rows = vec!["9".repeat(128)];
+        rows.extend((1..=8).rev().map(|h| h.to_string().repeat(128)));
+        rows.push("0".repeat(128));
+        let map = parse_input(&rows.join("\n")).unwrap();
+        let heads = trail_heads(&map);
+        assert_eq!(heads.len(), 128);
+        assert!(heads.iter().all(|head| head.score == 1 && head.rating == 1));
+    }
 }
@@ -1,79 +1,92 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
-use std::collections::HashMap;
-use std::string::ToString;
+use advent_of_code::memo::Memo;
+use advent_of_code::parse::{parse_all, space_separated, unsigned, ParseError};
 
-advent_of_code::solution!(11);
+advent_of_code::solution!(11, show = show);
+
+fn parse_input(input: &str) -> Result<Vec<u64>, ParseError> {
+    parse_all(input, space_separated(unsigned))
+}
+
+/// Splits a number with an even number of digits into its left and right half.
+fn split_digits(stone: u64) -> Option<(u64, u64)> {
+    let digits = stone.checked_ilog10()? + 1;
+    (digits % 2 == 0).then(|| {
+        let half = 10u64.pow(digits / 2);
+        (stone / half, stone % half)
+    })
+}
+
+/// The number of stones a single stone turns into after blinking `times` times, or [`None`] if
+/// a stone's number or the count overflows.
+fn count_stones(
+    memo: &mut Memo<(u64, usize), Option<usize>>,
+    stone: u64,
+    times: usize,
+) -> Option<usize> {
+    if times == 0 {
+        return Some(1);
+    }
+    memo.get_or_compute((stone, times), |memo| {
+        if stone == 0 {
+            count_stones(memo, 1, times - 1)
+        } else if let Some((left, right)) = split_digits(stone) {
+            count_stones(memo, left, times - 1)?.checked_add(count_stones(memo, right, times - 1)?)
+        } else {
+            count_stones(memo, stone.checked_mul(2024)?, times - 1)
+        }
+    })
+}
+
+fn blinking(stones: &[u64], times: usize) -> Option<usize> {
+    let mut memo = Memo::new();
+    stones.iter().try_fold(0usize, |total, &stone| {
+        total.checked_add(count_stones(&mut memo, stone, times)?)
+    })
+}
 
 #[must_use]
 pub fn part_one(input: &str) -> Option<usize> {
-    let stones = input
-        .split_whitespace()
-        .map(ToString::to_string)
-        .collect::<Vec<_>>();
-
+    let stones = parse_input(input).ok()?;
     blinking(&stones, 25)
 }
 
 #[must_use]
 pub fn part_two(input: &str) -> Option<usize> {
-    let stones = input
-        .split_whitespace()
-        .map(ToString::to_string)
-        .collect::<Vec<_>>();
-
+    let stones = parse_input(input).ok()?;
     blinking(&stones, 75)
 }
 
-fn blinking(stones: &[String], times: usize) -> Option<usize> {
-    let mut init_blinking_stones = HashMap::new();
-    for v in stones {
-        init_blinking_stones.insert(v.clone(), 1usize);
+/// Explains why the input could not be parsed, once after the last part.
+fn show(input: &str, part: u8) {
+    if !advent_of_code::template::is_last_part(part) {
+        return;
     }
-    for _ in 0..times {
-        let mut blinking_stones = HashMap::new();
-        for (stone, &times) in &init_blinking_stones {
-            let mut num = stone.parse::<u64>().ok()?;
-            // all numbers 0's will be converted to 1's
-            if num == 0 {
-                blinking_stones
-                    .entry("1".to_owned())
-                    .and_modify(|s| *s += times)
-                    .or_insert(times);
-            } else if stone.len() % 2 == 0 {
-                // if length is even, split the number in 2, taking into account
-                // edge cases
-                let (num1, num2) = stone.split_at(stone.len() / 2);
-                let (num1, num2) = (num1.trim_start_matches('0'), num2.trim_start_matches('0'));
-                for val in <[&str; 2]>::from((num1, num2)) {
-                    let n = if val.is_empty() {
-                        "0".to_owned()
-                    } else {
-                        val.to_owned()
-                    };
-                    blinking_stones
-                        .entry(n)
-                        .and_modify(|s| *s += times)
-                        .or_insert(times);
-                }
-            } else {
-                // else no special case, just multiply the number by 2024
-                num *= 2024;
-                blinking_stones
-                    .entry(num.to_string())
-                    .and_modify(|s| *s += times)
-                    .or_insert(times);
-            }
-        }
-        init_blinking_stones = blinking_stones;
+    if let Err(e) = parse_input(input) {
+        eprintln!("{e}");
     }
-
-    Some(init_blinking_stones.values().sum::<usize>())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_split_digits() {
+        assert_eq!(split_digits(1000), Some((10, 0)));
+        assert_eq!(split_digits(253_000), Some((253, 0)));
+        assert_eq!(split_digits(512), None);
+        assert_eq!(split_digits(0), None);
+    }
+
+    #[test]
+    fn test_blinking() {
+        assert_eq!(blinking(&[125, 17], 6), Some(22));
+        // an odd number of digits, and 2024 times it does not fit into a u64
+        assert_eq!(blinking(&[u64::MAX / 1000], 1), None);
+        assert_eq!(part_one("18446744073709551\n"), None);
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
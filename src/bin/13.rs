@@ -1,16 +1,16 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
-advent_of_code::solution!(13);
+advent_of_code::solution!(13, show = show);
 
+use advent_of_code::linalg::{solve, Solution};
+use advent_of_code::parse::{blocks, parse_all, signed, ParseError};
+use advent_of_code::rational::Rational;
 use nom::{
     bytes::complete::tag,
-    character::complete::{anychar, digit1, newline},
-    combinator::map_res,
-    multi::separated_list1,
+    character::complete::{anychar, line_ending},
     sequence::preceded,
     IResult,
 };
-
-use std::str::FromStr;
+use std::fmt;
 
 #[derive(Debug)]
 struct Button {
@@ -31,32 +31,28 @@ struct Entry {
     prize: Prize,
 }
 
-fn parse_integer(input: &str) -> IResult<&str, i64> {
-    map_res(digit1, |s: &str| i64::from_str(s))(input)
-}
-
 fn parse_button(input: &str) -> IResult<&str, Button> {
     let (input, _) = preceded(tag("Button "), anychar)(input)?;
-    let (input, _) = tag(": X+")(input)?;
-    let (input, x) = parse_integer(input)?;
-    let (input, _) = tag(", Y+")(input)?;
-    let (input, y) = parse_integer(input)?;
+    let (input, _) = tag(": X")(input)?;
+    let (input, x) = signed(input)?;
+    let (input, _) = tag(", Y")(input)?;
+    let (input, y) = signed(input)?;
     Ok((input, Button { x, y }))
 }
 
 fn parse_prize(input: &str) -> IResult<&str, Prize> {
     let (input, _) = tag("Prize: X=")(input)?;
-    let (input, x) = parse_integer(input)?;
+    let (input, x) = signed(input)?;
     let (input, _) = tag(", Y=")(input)?;
-    let (input, y) = parse_integer(input)?;
+    let (input, y) = signed(input)?;
     Ok((input, Prize { x, y }))
 }
 
 fn parse_entry(input: &str) -> IResult<&str, Entry> {
     let (input, button_a) = parse_button(input)?;
-    let (input, _) = newline(input)?;
+    let (input, _) = line_ending(input)?;
     let (input, button_b) = parse_button(input)?;
-    let (input, _) = newline(input)?;
+    let (input, _) = line_ending(input)?;
     let (input, prize) = parse_prize(input)?;
     Ok((
         input,
@@ -68,41 +64,154 @@ fn parse_entry(input: &str) -> IResult<&str, Entry> {
     ))
 }
 
-fn parse_entries(input: &str) -> IResult<&str, Vec<Entry>> {
-    separated_list1(tag("\n\n"), parse_entry)(input)
+fn parse_entries(input: &str) -> Result<Vec<Entry>, ParseError> {
+    parse_all(input, blocks(parse_entry))
+}
+
+/// Tokens needed to press the A and B buttons once.
+const COSTS: [i128; 2] = [3, 1];
+
+/// How much further away the prizes are in part two, along both axes.
+const PART_TWO_INCREMENT: i64 = 10_000_000_000_000;
+
+/// How to win a prize with the fewest tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Presses {
+    a: i128,
+    b: i128,
+    tokens: i128,
+}
+
+/// Why a prize cannot be won.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unwinnable {
+    /// The buttons move the claw along the same line, and the prize is off it.
+    OffLine,
+    /// The only way to reach the prize takes a fractional number of presses.
+    Fractional,
+    /// The only way to reach the prize takes a negative number of presses.
+    Negative,
+    /// The buttons move the claw along the same line as the prize, but no whole, non-negative
+    /// number of presses stops on it.
+    Missed,
 }
 
-fn compute_cost(entries: &mut [Entry], increment: i64) -> i64{
-    for entry in entries.iter_mut() {
-        entry.prize.x += increment;
-        entry.prize.y += increment;
+impl fmt::Display for Unwinnable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            Self::OffLine => "prize is off the line the claw moves along",
+            Self::Fractional => "needs a fractional number of presses",
+            Self::Negative => "needs a negative number of presses",
+            Self::Missed => "no whole number of presses stops on the prize",
+        };
+        write!(f, "{reason}")
     }
-    let mut total_cost = 0;
-
-    for entry in entries {
-        let da =
-            (entry.button_a.x * entry.button_b.y - entry.button_a.y * entry.button_b.x).abs();
-        let dx = (entry.prize.x * entry.button_b.y - entry.prize.y * entry.button_b.x).abs();
-        let dy = (entry.button_a.x * entry.prize.y - entry.button_a.y * entry.prize.x).abs();
-        if dx % da == 0 && dy % da == 0 {
-            let x = dx / da;
-            let y = dy / da;
-            total_cost += x * 3 + y;
+}
+
+/// The presses that win the prize with the fewest tokens.
+///
+/// Each coordinate gives one equation over the number of A and B presses. If the buttons move the
+/// claw along the same line, the equations have a whole family of solutions and the cheapest
+/// non-negative one is picked.
+fn play(entry: &Entry, increment: i64) -> Result<Presses, Unwinnable> {
+    let coefficients = [
+        vec![entry.button_a.x.into(), entry.button_b.x.into()],
+        vec![entry.button_a.y.into(), entry.button_b.y.into()],
+    ];
+    let rhs = [
+        (entry.prize.x + increment).into(),
+        (entry.prize.y + increment).into(),
+    ];
+    let solution = solve(&coefficients, &rhs);
+    match &solution {
+        Solution::None => return Err(Unwinnable::OffLine),
+        Solution::Unique(values) => {
+            if values.iter().any(|value| value.to_integer().is_none()) {
+                return Err(Unwinnable::Fractional);
+            }
+            if values.iter().any(|&value| value < Rational::ZERO) {
+                return Err(Unwinnable::Negative);
+            }
+        }
+        // neither button moves the claw, and the prize is right where it starts.
+        Solution::Parametric(family) if family.free.len() == 2 => {
+            return Ok(Presses {
+                a: 0,
+                b: 0,
+                tokens: 0,
+            });
+        }
+        Solution::Parametric(_) => {}
+    }
+    match solution.min_cost_non_negative(&COSTS) {
+        Some((presses, tokens)) => Ok(Presses {
+            a: presses[0],
+            b: presses[1],
+            tokens,
+        }),
+        None => Err(Unwinnable::Missed),
+    }
+}
+
+/// Prints how each machine is won, or why it cannot be.
+fn report(entries: &[Entry], increment: i64) {
+    println!(
+        "{:>7}  {:>33}  {:>15}  {:>15}  {:>16}",
+        "machine", "prize", "A", "B", "tokens"
+    );
+    for (idx, entry) in entries.iter().enumerate() {
+        let prize = format!(
+            "({}, {})",
+            entry.prize.x + increment,
+            entry.prize.y + increment
+        );
+        print!("{:>7}  {prize:>33}  ", idx + 1);
+        match play(entry, increment) {
+            Ok(presses) => println!(
+                "{:>15}  {:>15}  {:>16}",
+                presses.a, presses.b, presses.tokens
+            ),
+            Err(reason) => println!("{reason}"),
         }
     }
+}
 
-    total_cost
+fn compute_cost(entries: &[Entry], increment: i64) -> Option<i64> {
+    let total = entries
+        .iter()
+        .filter_map(|entry| play(entry, increment).ok())
+        .map(|presses| presses.tokens)
+        .sum::<i128>();
+    total.try_into().ok()
 }
 
-#[must_use] pub fn part_one(input: &str) -> Option<i64> {
-    let (_, mut entries) = parse_entries(input).unwrap_or_default();
-    Some(compute_cost(&mut entries, 0))
+#[must_use]
+pub fn part_one(input: &str) -> Option<i64> {
+    let entries = parse_entries(input).ok()?;
+    compute_cost(&entries, 0)
 }
 
+#[must_use]
+pub fn part_two(input: &str) -> Option<i64> {
+    let entries = parse_entries(input).ok()?;
+    compute_cost(&entries, PART_TWO_INCREMENT)
+}
 
-#[must_use] pub fn part_two(input: &str) -> Option<i64> {
-    let (_, mut entries) = parse_entries(input).unwrap_or_default();
-    Some(compute_cost(&mut entries, 10_000_000_000_000))
+/// Reports every machine of the part with `--report`. Explains why the input could not be
+/// parsed once after the last part.
+fn show(input: &str, part: u8) {
+    let entries = match parse_entries(input) {
+        Ok(entries) => entries,
+        Err(e) => {
+            if advent_of_code::template::is_last_part(part) {
+                eprintln!("{e}");
+            }
+            return;
+        }
+    };
+    if std::env::args().any(|x| x == "--report") {
+        report(&entries, if part == 1 { 0 } else { PART_TWO_INCREMENT });
+    }
 }
 
 #[cfg(test)]
@@ -120,4 +229,89 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(875_318_608_908));
     }
+
+    #[test]
+    fn test_parse_entries() {
+        let entries =
+            parse_entries("Button A: X+2, Y-1\nButton B: X-3, Y+4\nPrize: X=-5, Y=10\n").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!((entries[0].button_a.x, entries[0].button_a.y), (2, -1));
+        assert_eq!((entries[0].button_b.x, entries[0].button_b.y), (-3, 4));
+        assert_eq!((entries[0].prize.x, entries[0].prize.y), (-5, 10));
+
+        // a malformed machine fails instead of costing nothing
+        assert!(parse_entries("Button A: X+2, Y+1\nButton B: X+3\nPrize: X=5, Y=10").is_err());
+    }
+
+    #[test]
+    fn test_collinear_buttons() {
+        let entries = parse_entries(
+            "Button A: X+2, Y+2\nButton B: X+1, Y+1\nPrize: X=4, Y=4\n\n\
+             Button A: X+3, Y+3\nButton B: X+2, Y+2\nPrize: X=7, Y=7\n\n\
+             Button A: X+2, Y+2\nButton B: X+1, Y+1\nPrize: X=4, Y=5",
+        )
+        .unwrap();
+        // B alone is cheaper per step
+        assert_eq!(
+            play(&entries[0], 0),
+            Ok(Presses {
+                a: 0,
+                b: 4,
+                tokens: 4
+            })
+        );
+        // B alone cannot reach 7, only 1 A and 2 B presses can
+        assert_eq!(
+            play(&entries[1], 0),
+            Ok(Presses {
+                a: 1,
+                b: 2,
+                tokens: 5
+            })
+        );
+        // the prize is off the line the claw moves along
+        assert_eq!(play(&entries[2], 0), Err(Unwinnable::OffLine));
+    }
+
+    #[test]
+    fn test_per_machine_results() {
+        let entries = parse_entries(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let outcomes = entries
+            .iter()
+            .map(|entry| play(entry, 0))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outcomes,
+            [
+                Ok(Presses {
+                    a: 80,
+                    b: 40,
+                    tokens: 280
+                }),
+                Err(Unwinnable::Fractional),
+                Ok(Presses {
+                    a: 38,
+                    b: 86,
+                    tokens: 200
+                }),
+                Err(Unwinnable::Fractional),
+            ]
+        );
+        assert!(play(&entries[1], PART_TWO_INCREMENT).is_ok());
+    }
+
+    #[test]
+    fn test_degenerate_machines() {
+        let entries = parse_entries(
+            "Button A: X+2, Y+1\nButton B: X+1, Y+2\nPrize: X=1, Y=-1\n\n\
+             Button A: X+4, Y+4\nButton B: X+6, Y+6\nPrize: X=5, Y=5\n\n\
+             Button A: X+0, Y+0\nButton B: X+0, Y+0\nPrize: X=0, Y=0\n\n\
+             Button A: X+0, Y+0\nButton B: X+0, Y+0\nPrize: X=1, Y=0",
+        )
+        .unwrap();
+        assert_eq!(play(&entries[0], 0), Err(Unwinnable::Negative));
+        assert_eq!(play(&entries[1], 0), Err(Unwinnable::Missed));
+        assert_eq!(play(&entries[2], 0).map(|presses| presses.tokens), Ok(0));
+        assert_eq!(play(&entries[3], 0), Err(Unwinnable::OffLine));
+    }
 }
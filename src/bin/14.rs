@@ -1,9 +1,28 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
-use std::collections::HashMap;
+use advent_of_code::grid::Grid;
+use advent_of_code::math::crt;
+use advent_of_code::parse::{labelled_pair, lines, parse_all, ParseError};
+use advent_of_code::render::{render, write_pbm, Player, Rgb, Tile};
+use nom::{
+    character::complete::{line_ending, space1},
+    combinator::{map, opt},
+    sequence::{pair, separated_pair, terminated},
+};
+use std::fmt;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
 
-advent_of_code::solution!(14);
+advent_of_code::solution!(14, show = show);
 
-#[derive(Debug, PartialEq, Eq)]
+/// The size of the space for the real input. Inputs of another size, like the 11 by 7 examples,
+/// declare theirs in a `size=<width>,<height>` line before the robots.
+const SPACE_SIZE: (i32, i32) = (101, 103);
+
+/// How many seconds before the picture `--animate` starts.
+const ANIMATION_SECONDS: i32 = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Robot {
     pos: (i32, i32),
     velocity: (i32, i32),
@@ -14,72 +33,89 @@ struct Space {
     robots: Vec<Robot>,
     width: i32,
     height: i32,
-    robots_in_pos: HashMap<(i32, i32), u64>,
-}
-
-fn parse_input(input: &str) -> Option<Space> {
-    let mut robots = Vec::new();
-    let mut robots_in_pos = HashMap::new();
-
-    let mut height = i32::MIN;
-    let mut width = i32::MIN;
-    for line in input.lines() {
-        let (pos, velocity) = line.split_once(char::is_whitespace)?;
-        let pos = pos.strip_prefix("p=")?.split_once(',')?;
-        let pos = (pos.0.parse::<i32>().ok()?, pos.1.parse::<i32>().ok()?);
-        let (x, y) = pos;
-        if x > width {
-            width = x;
-        }
-        if y > height {
-            height = y;
-        }
-
-        let velocity = velocity.strip_prefix("v=")?.split_once(',')?;
-        let velocity = (
-            velocity.0.parse::<i32>().ok()?,
-            velocity.1.parse::<i32>().ok()?,
-        );
+}
 
-        robots_in_pos
-            .entry(pos)
-            .and_modify(|count| *count += 1)
-            .or_insert_with(|| 1);
+/// Why an input does not describe a space with robots in it.
+#[derive(Debug, PartialEq, Eq)]
+enum InvalidInput {
+    Parse(ParseError),
+    Empty {
+        width: i32,
+        height: i32,
+    },
+    Outside {
+        pos: (i32, i32),
+        width: i32,
+        height: i32,
+    },
+}
 
-        robots.push(Robot { pos, velocity });
+impl fmt::Display for InvalidInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{e}"),
+            Self::Empty { width, height } => write!(f, "space of {width}x{height} tiles is empty"),
+            Self::Outside { pos, width, height } => write!(
+                f,
+                "robot at {},{} is outside the space of {width}x{height} tiles",
+                pos.0, pos.1
+            ),
+        }
     }
+}
 
-    width += 1;
-    height += 1;
+fn parse_input(input: &str) -> Result<Space, InvalidInput> {
+    let size = terminated(labelled_pair("size"), line_ending);
+    let robot = map(
+        separated_pair(labelled_pair("p"), space1, labelled_pair("v")),
+        |(pos, velocity)| Robot { pos, velocity },
+    );
+    let (size, robots) =
+        parse_all(input, pair(opt(size), lines(robot))).map_err(InvalidInput::Parse)?;
 
-    Some(Space {
+    let (width, height) = size.unwrap_or(SPACE_SIZE);
+    if width <= 0 || height <= 0 {
+        return Err(InvalidInput::Empty { width, height });
+    }
+    if let Some(robot) = robots
+        .iter()
+        .find(|robot| !(0..width).contains(&robot.pos.0) || !(0..height).contains(&robot.pos.1))
+    {
+        return Err(InvalidInput::Outside {
+            pos: robot.pos,
+            width,
+            height,
+        });
+    }
+    Ok(Space {
         robots,
         width,
         height,
-        robots_in_pos,
     })
 }
 
-fn navigate(space: &mut Space) {
-    for robot in &mut space.robots {
-        let (x, y) = robot.pos;
-        space
-            .robots_in_pos
-            .entry((x, y))
-            .and_modify(|count| *count -= 1);
-        robot.pos = (
-            (x + robot.velocity.0 + space.width) % space.width,
-            (y + robot.velocity.1 + space.height) % space.height,
-        );
-        space
-            .robots_in_pos
-            .entry(robot.pos)
-            .and_modify(|count| *count += 1)
-            .or_insert_with(|| 1);
-    }
+/// Where something starting at `pos` and moving at `velocity` along an axis of `len` tiles is
+/// after `seconds`. It is back at `pos` every `len` seconds.
+const fn wrap(pos: i32, velocity: i32, seconds: i32, len: i32) -> i32 {
+    (pos + velocity.rem_euclid(len) * seconds.rem_euclid(len)).rem_euclid(len)
+}
 
-    // remove 0 count robots
-    space.robots_in_pos.retain(|&_, &mut v| v > 0);
+impl Space {
+    /// The space after `seconds`, computed for each robot directly.
+    fn after(&self, seconds: i32) -> Self {
+        let robots = self
+            .robots
+            .iter()
+            .map(|robot| Robot {
+                pos: (
+                    wrap(robot.pos.0, robot.velocity.0, seconds, self.width),
+                    wrap(robot.pos.1, robot.velocity.1, seconds, self.height),
+                ),
+                velocity: robot.velocity,
+            })
+            .collect();
+        Self { robots, ..*self }
+    }
 }
 
 fn count_robots(space: &Space) -> usize {
@@ -104,35 +140,204 @@ fn count_robots(space: &Space) -> usize {
     q1 * q2 * q3 * q4
 }
 
-#[must_use] pub fn part_one(input: &str) -> Option<u64> {
-    let mut space = parse_input(input)?;
-    for _ in 0..100 {
-        navigate(&mut space);
+/// The variance of `values`, scaled by their squared number to stay in integers.
+fn variance(values: impl Iterator<Item = i32>) -> i64 {
+    let (n, sum, sum_of_squares) = values.fold((0, 0, 0), |(n, sum, squares), value| {
+        let value = i64::from(value);
+        (n + 1, sum + value, squares + value * value)
+    });
+    n * sum_of_squares - sum.pow(2)
+}
+
+/// The second in `0..len` at which the robots are least spread out along one axis of `len` tiles,
+/// where `axis` picks a robot's position and velocity along it.
+///
+/// Robots moving at random spread out over the whole space, the picture packs most of them into
+/// a small area, so the picture shows up at the second with the lowest variance.
+fn least_spread(robots: &[Robot], len: i32, axis: fn(&Robot) -> (i32, i32)) -> i32 {
+    (0..len)
+        .min_by_key(|&seconds| {
+            variance(robots.iter().map(|robot| {
+                let (pos, velocity) = axis(robot);
+                wrap(pos, velocity, seconds, len)
+            }))
+        })
+        .unwrap_or(0)
+}
+
+/// The robots as a bitmap, one row per y coordinate. Robots outside the space are left out.
+fn picture(space: &Space) -> Grid<bool> {
+    let len = |len| usize::try_from(len).unwrap_or(0);
+    let mut grid = Grid::new(len(space.height), len(space.width), false);
+    for robot in &space.robots {
+        let (Ok(x), Ok(y)) = (usize::try_from(robot.pos.0), usize::try_from(robot.pos.1)) else {
+            continue;
+        };
+        if let Some(tile) = grid.get_mut((y, x)) {
+            *tile = true;
+        }
     }
+    grid
+}
+
+/// The x coordinates repeat every `width` seconds and the y coordinates every `height` seconds,
+/// so the picture shows up at the second that is at the best offset in both periods.
+fn picture_second(space: &Space) -> Option<i32> {
+    let x = least_spread(&space.robots, space.width, |robot| {
+        (robot.pos.0, robot.velocity.0)
+    });
+    let y = least_spread(&space.robots, space.height, |robot| {
+        (robot.pos.1, robot.velocity.1)
+    });
+    let (seconds, _) = crt([
+        (x.into(), space.width.into()),
+        (y.into(), space.height.into()),
+    ])?;
 
-    Some(count_robots(&space) as u64)
+    i32::try_from(seconds).ok()
 }
 
-#[must_use] pub fn part_two(input: &str) -> Option<u64> {
-    let mut space = parse_input(input)?;
-    let mut seconds = 0;
-    for i in 0..10000 {
-        navigate(&mut space);
-        // if there are no more overlapping robots on the same position,
-        // we found the tree
-        if space.robots_in_pos.values().all(|&v| v == 1) {
-            seconds = i + 1;
-            break;
+/// The picture as a frame for the terminal.
+fn draw(picture: &Grid<bool>) -> String {
+    render(picture, |_, &robot| {
+        if robot {
+            Tile::new('#').fg(Rgb::GREEN)
+        } else {
+            Tile::new('.').fg(Rgb::GREY)
         }
+    })
+}
+
+/// After part two, prints the picture with `--display`, writes it as a PBM image with
+/// `--image <path>` and plays the seconds leading up to it with `--animate`. Explains why the
+/// input is invalid once after the last part.
+fn show(input: &str, part: u8) {
+    let space = match parse_input(input) {
+        Ok(space) => space,
+        Err(e) => {
+            if advent_of_code::template::is_last_part(part) {
+                eprintln!("{e}");
+            }
+            return;
+        }
+    };
+    if part != 2 {
+        return;
+    }
+    let display = std::env::args().any(|x| x == "--display");
+    let animate = std::env::args().any(|x| x == "--animate");
+    let path = pico_args::Arguments::from_env()
+        .opt_value_from_str::<_, PathBuf>("--image")
+        .unwrap_or_else(|e| {
+            eprintln!("{e}");
+            None
+        });
+    if !display && !animate && path.is_none() {
+        return;
+    }
+    let Some(seconds) = picture_second(&space) else {
+        return;
+    };
+    if animate {
+        let frames = (seconds - ANIMATION_SECONDS).max(0)..=seconds;
+        Player::with_fps(4.0)
+            .interactive()
+            .play(frames.map(|second| {
+                format!(
+                    "after {second} seconds\n{}",
+                    draw(&picture(&space.after(second)))
+                )
+            }));
+    }
+    let picture = picture(&space.after(seconds));
+    if display {
+        println!("{}", draw(&picture));
     }
+    if let Some(path) = path {
+        let written = File::create(&path)
+            .and_then(|file| write_pbm(&picture, 4, |&robot| robot, &mut BufWriter::new(file)));
+        if let Err(e) = written {
+            eprintln!("could not write {}: {e}", path.display());
+        }
+    }
+}
 
-    Some(seconds)
+#[must_use] pub fn part_one(input: &str) -> Option<u64> {
+    let space = parse_input(input).ok()?;
+    Some(count_robots(&space.after(100)) as u64)
+}
+
+#[must_use] pub fn part_two(input: &str) -> Option<u64> {
+    let space = parse_input(input).ok()?;
+    u64::try_from(picture_second(&space)?).ok()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// An input for a space of the real size where the robots form a tree after `seconds`, and
+    /// another `noise` robots wander around.
+    fn hidden_tree(seconds: i32, noise: i32) -> String {
+        let (width, height) = SPACE_SIZE;
+        let tree = (0..10)
+            .flat_map(|row| (-row..=row).map(move |col| (50 + col, 40 + row)))
+            .chain((0..3).map(|row| (50, 50 + row)));
+        let scattered = (0..noise).map(|i| ((i * 37) % width, (i * 53) % height));
+
+        tree.chain(scattered)
+            .zip(1..)
+            .map(|((x, y), i)| {
+                let (vx, vy) = ((i * 13) % (width - 1) + 1, -((i * 29) % (height - 1) + 1));
+                let start = (
+                    (x - vx * seconds).rem_euclid(width),
+                    (y - vy * seconds).rem_euclid(height),
+                );
+                format!("p={},{} v={vx},{vy}\n", start.0, start.1)
+            })
+            .collect::<Vec<_>>()
+            .concat()
+    }
+
+    #[test]
+    fn test_parse_size() {
+        let space = parse_input(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        assert_eq!((space.width, space.height), (11, 7));
+        assert_eq!(space.robots.len(), 12);
+
+        let space = parse_input("p=0,4 v=3,-3\n").unwrap();
+        assert_eq!((space.width, space.height), SPACE_SIZE);
+        assert_eq!(
+            parse_input("size=0,7\np=0,4 v=3,-3\n"),
+            Err(InvalidInput::Empty {
+                width: 0,
+                height: 7
+            })
+        );
+        assert_eq!(
+            parse_input("size=11,7\np=11,4 v=3,-3\n")
+                .unwrap_err()
+                .to_string(),
+            "robot at 11,4 is outside the space of 11x7 tiles"
+        );
+        assert!(parse_input("size=11,7\np=0,-1 v=3,-3\n").is_err());
+        assert!(matches!(
+            parse_input("size=11,7\np=0 v=3,-3\n"),
+            Err(InvalidInput::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_after() {
+        let space = parse_input("size=11,7\np=2,4 v=2,-3\n").unwrap();
+        let positions = (0..=5)
+            .map(|seconds| space.after(seconds).robots[0].pos)
+            .collect::<Vec<_>>();
+        assert_eq!(positions, [(2, 4), (4, 1), (6, 5), (8, 2), (10, 6), (1, 3)]);
+        assert_eq!(space.after(77 * 1_000_000 + 5), space.after(5));
+        assert_eq!(space.after(-1), space.after(76));
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
@@ -141,7 +346,21 @@ mod tests {
 
     #[test]
     fn test_part_two() {
-        let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(1));
+        assert_eq!(part_two(&hidden_tree(7_371, 200)), Some(7_371));
+        assert_eq!(part_two(&hidden_tree(0, 200)), Some(0));
+
+        let space = parse_input(&hidden_tree(2_024, 100)).unwrap();
+        let picture = picture(&space.after(2_024));
+        assert!(picture[(40, 50)] && picture[(49, 41)] && picture[(52, 50)]);
+
+        let outside = Space {
+            robots: vec![Robot {
+                pos: (11, -1),
+                velocity: (0, 0),
+            }],
+            width: 11,
+            height: 7,
+        };
+        assert!(super::picture(&outside).iter().all(|(_, &robot)| !robot));
     }
 }
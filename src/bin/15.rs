@@ -1,26 +1,14 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
-advent_of_code::solution!(15);
-use std::{
-    collections::VecDeque,
-    ops::{Add, Index, IndexMut},
-};
-
-#[repr(usize)]
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
-enum Direction {
-    Up = 0,
-    Down = 1,
-    Left = 2,
-    Right = 3,
-}
-
-#[derive(Ord, PartialOrd, Default, Debug, PartialEq, Eq, Copy, Clone, Hash)]
-struct Pos(i32, i32);
-
-#[derive(Default, Debug, PartialEq, Eq, Clone)]
-struct Warehouse(Vec<Vec<char>>);
+advent_of_code::solution!(15, show = show);
+use advent_of_code::geometry::{Direction, Point};
+use advent_of_code::grid::Grid;
+use advent_of_code::render::{render, Rgb, Tile, ANSI_CLEAR};
+use std::collections::VecDeque;
+use std::io::{self, Write};
 
-const DIRECTIONS: [Pos; 4] = [Pos(-1, 0), Pos(1, 0), Pos(0, -1), Pos(0, 1)];
+/// A grid of `#` walls, `.` floor, the robot `@` and boxes. A box one tile wide is an `O`, wider
+/// boxes are a `[`, any number of `=` and a `]`, e.g. `[]` or `[==]`.
+type Warehouse = Grid<char>;
 
 #[derive(Debug, Eq, PartialEq)]
 struct Document {
@@ -28,154 +16,139 @@ struct Document {
     moves: Vec<Direction>,
 }
 
-impl Add for Pos {
-    type Output = Self;
+/// Which tile of a box counts for its GPS coordinate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Gps {
+    LeftEdge,
+    /// The middle tile, or the left one of the two middle tiles of a box of even width.
+    Centre,
+}
 
-    fn add(self, other: Self) -> Self {
-        Self(self.0 + other.0, self.1 + other.1)
-    }
+/// A variant of the puzzle: the warehouse is widened `scale` times and boxes are scored by `gps`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Config {
+    scale: usize,
+    gps: Gps,
 }
 
-// Implement the Index trait for Warehose
-impl Index<Pos> for Warehouse {
-    type Output = char;
+const PART_ONE: Config = Config {
+    scale: 1,
+    gps: Gps::LeftEdge,
+};
+const PART_TWO: Config = Config {
+    scale: 2,
+    gps: Gps::LeftEdge,
+};
 
-    fn index(&self, index: Pos) -> &Self::Output {
-        &self.0[usize::try_from(index.0).unwrap_or_default()]
-            [usize::try_from(index.1).unwrap_or_default()]
+impl Gps {
+    fn parse(s: &str) -> Result<Self, &'static str> {
+        match s {
+            "left" => Ok(Self::LeftEdge),
+            "centre" => Ok(Self::Centre),
+            _ => Err("expecting left or centre"),
+        }
     }
 }
 
-// Implement the IndexMut trait for Warehose
-impl IndexMut<Pos> for Warehouse {
-    fn index_mut(&mut self, index: Pos) -> &mut Self::Output {
-        &mut self.0[usize::try_from(index.0).unwrap_or_default()]
-            [usize::try_from(index.1).unwrap_or_default()]
+impl Config {
+    /// Overrides the configuration of a part with `--scale <k>` and `--gps left|centre`.
+    fn with_args(self) -> Self {
+        let mut args = pico_args::Arguments::from_env();
+        let scale = args.opt_value_from_fn("--scale", |s| match s.parse() {
+            Ok(scale @ 1..) => Ok(scale),
+            _ => Err("expecting a scale of at least 1"),
+        });
+        let gps = args.opt_value_from_fn("--gps", Gps::parse);
+        match (scale, gps) {
+            (Ok(scale), Ok(gps)) => Self {
+                scale: scale.unwrap_or(self.scale),
+                gps: gps.unwrap_or(self.gps),
+            },
+            (Err(e), _) | (_, Err(e)) => {
+                eprintln!("{e}");
+                self
+            }
+        }
     }
 }
 
-fn parse_input(input: &str) -> Document {
-    use Direction::{Down, Left, Right, Up};
-    let mut warehouse = Vec::new();
-    let mut moves = Vec::new();
-    let mut moves_flag = false;
-    for line in input.lines() {
-        if line.is_empty() {
-            moves_flag = true;
-            continue;
-        }
+fn parse_input(input: &str) -> Option<Document> {
+    let (warehouse, moves) = input.split_once("\n\n").unwrap_or((input, ""));
+    let moves = moves
+        .lines()
+        .flat_map(str::chars)
+        .map(Direction::try_from)
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+
+    Some(Document {
+        warehouse: warehouse.parse().ok()?,
+        moves,
+    })
+}
 
-        if moves_flag {
-            moves.extend(line.chars().map(|c| {
-                if c == '<' {
-                    Left
-                } else if c == '>' {
-                    Right
-                } else if c == '^' {
-                    Up
-                } else {
-                    Down
-                }
-            }));
-        } else {
-            warehouse.push(line.chars().collect());
-        }
+const fn is_box(tile: char) -> bool {
+    matches!(tile, 'O' | '[' | '=' | ']')
+}
+
+/// The tiles of a box of the given width.
+fn box_of_width(width: usize) -> Vec<char> {
+    if width == 1 {
+        return vec!['O'];
     }
+    let mut tiles = vec!['='; width];
+    tiles[0] = '[';
+    tiles[width - 1] = ']';
+    tiles
+}
 
-    Document {
-        warehouse: Warehouse(warehouse),
-        moves,
+/// All tiles of the box at `pos`: `pos` first, then the tiles to its left going outwards, then
+/// the tiles to its right.
+fn box_tiles(warehouse: &Warehouse, pos: Point) -> Vec<Point> {
+    use Direction::{Left, Right};
+    let mut tiles = vec![pos];
+    let mut left = pos;
+    while matches!(warehouse[left], '=' | ']') {
+        left += Left;
+        tiles.push(left);
     }
+    let mut right = pos;
+    while matches!(warehouse[right], '[' | '=') {
+        right += Right;
+        tiles.push(right);
+    }
+    tiles
 }
 
-fn stick_robot_to_boxes2(warehouse: &Warehouse, pos: Pos, dir: Direction) -> Vec<Pos> {
+/// The robot at `pos` and all box tiles it would push in `dir`, ordered so that moving them one
+/// by one never moves a tile onto another one that still has to move.
+fn stick_robot_to_boxes2(warehouse: &Warehouse, pos: Point, dir: Direction) -> Vec<Point> {
     use Direction::{Down, Left, Right, Up};
     // the robot included is sticked to the a list of boxes
     let mut sticked = Vec::from([pos]);
-    let mut curr_boxes_per_level = VecDeque::from([pos]);
     match dir {
-        Up => {
-            while !curr_boxes_per_level.is_empty() {
-                let Some(curr_pos) = curr_boxes_per_level.pop_front() else {
-                    return sticked;
-                };
-                let next = curr_pos + DIRECTIONS[Up as usize];
-                let next_tile = warehouse[next];
-                if next_tile == '[' {
-                    let right = next + DIRECTIONS[Right as usize];
-                    if !sticked.contains(&next) {
-                        sticked.push(next);
-                        curr_boxes_per_level.push_back(next);
-                    }
-                    if !sticked.contains(&right) {
-                        sticked.push(right);
-                        curr_boxes_per_level.push_back(right);
-                    }
-                } else if next_tile == ']' {
-                    let left = next + DIRECTIONS[Left as usize];
-                    if !sticked.contains(&next) {
-                        sticked.push(next);
-                        curr_boxes_per_level.push_back(next);
-                    }
-                    if !sticked.contains(&left) {
-                        sticked.push(left);
-                        curr_boxes_per_level.push_back(left);
-                    }
-                } else if next_tile == 'O' && !sticked.contains(&next) {
-                    sticked.push(next);
-                    curr_boxes_per_level.push_back(next);
+        Up | Down => {
+            // whole boxes are pushed, so a box can push several boxes of the next row and be
+            // pushed by several boxes of the previous one.
+            let mut curr_boxes_per_level = VecDeque::from([pos]);
+            while let Some(curr_pos) = curr_boxes_per_level.pop_front() {
+                let next = curr_pos + dir;
+                if !is_box(warehouse[next]) || sticked.contains(&next) {
+                    continue;
                 }
-            }
-        }
-        Down => {
-            while !curr_boxes_per_level.is_empty() {
-                let Some(curr_pos) = curr_boxes_per_level.pop_front() else {
-                    return sticked;
-                };
-                let next = curr_pos + DIRECTIONS[Down as usize];
-                let next_tile = warehouse[next];
-                if next_tile == '[' {
-                    let right = next + DIRECTIONS[Right as usize];
-                    if !sticked.contains(&next) {
-                        sticked.push(next);
-                        curr_boxes_per_level.push_back(next);
+                for tile in box_tiles(warehouse, next) {
+                    if !sticked.contains(&tile) {
+                        sticked.push(tile);
+                        curr_boxes_per_level.push_back(tile);
                     }
-                    if !sticked.contains(&right) {
-                        sticked.push(right);
-                        curr_boxes_per_level.push_back(right);
-                    }
-                } else if next_tile == ']' {
-                    let left = next + DIRECTIONS[Left as usize];
-                    if !sticked.contains(&next) {
-                        sticked.push(next);
-                        curr_boxes_per_level.push_back(next);
-                    }
-                    if !sticked.contains(&left) {
-                        sticked.push(left);
-                        curr_boxes_per_level.push_back(left);
-                    }
-                } else if next_tile == 'O' && !sticked.contains(&next) {
-                    sticked.push(next);
-                    curr_boxes_per_level.push_back(next);
                 }
             }
         }
-        Left => {
-            let mut next = pos + DIRECTIONS[Left as usize];
-            let mut next_tile = warehouse[next];
-            while next_tile == '[' || next_tile == ']' || next_tile == 'O' {
+        Left | Right => {
+            let mut next = pos + dir;
+            while is_box(warehouse[next]) {
                 sticked.push(next);
-                next = next + DIRECTIONS[Left as usize];
-                next_tile = warehouse[next];
-            }
-        }
-        Right => {
-            let mut next = pos + DIRECTIONS[Right as usize];
-            let mut next_tile = warehouse[next];
-            while next_tile == '[' || next_tile == ']' || next_tile == 'O' {
-                sticked.push(next);
-                next = next + DIRECTIONS[Right as usize];
-                next_tile = warehouse[next];
+                next += dir;
             }
         }
     }
@@ -184,14 +157,14 @@ fn stick_robot_to_boxes2(warehouse: &Warehouse, pos: Pos, dir: Direction) -> Vec
     sticked
 }
 
-fn move_robot_in_dir(warehouse: &mut Warehouse, pos: &mut Pos, dir: Direction) {
+fn move_robot_in_dir(warehouse: &mut Warehouse, pos: &mut Point, dir: Direction) {
     let sticked = stick_robot_to_boxes2(warehouse, *pos, dir);
 
     if can_boxes_move_in_dir(warehouse, &sticked, dir) {
         // we are able to move in this direction, so move all sticked boxes if any and the
         // robot
         for &p in &sticked {
-            let next = p + DIRECTIONS[dir as usize];
+            let next = p + dir;
             warehouse[next] = warehouse[p];
             warehouse[p] = '.';
             if warehouse[next] == '@' {
@@ -201,146 +174,345 @@ fn move_robot_in_dir(warehouse: &mut Warehouse, pos: &mut Pos, dir: Direction) {
     }
 }
 
-fn move_robot(document: &mut Document, pos: &mut Pos) {
+fn move_robot(document: &mut Document, pos: &mut Point) {
     for &dir in &document.moves {
         move_robot_in_dir(&mut document.warehouse, pos, dir);
     }
 }
 
-fn can_boxes_move_in_dir(warehouse: &Warehouse, sticked_boxes: &Vec<Pos>, dir: Direction) -> bool {
-    use Direction::{Down, Left, Right, Up};
-    match dir {
-        Up => {
-            for &pos in sticked_boxes {
-                let next = pos + DIRECTIONS[Up as usize];
-                let next_tile = warehouse[next];
-                if next_tile == '#' {
-                    return false;
-                }
-            }
-        }
-        Down => {
-            for &pos in sticked_boxes {
-                let next = pos + DIRECTIONS[Down as usize];
-                let next_tile = warehouse[next];
-                if next_tile == '#' {
-                    return false;
-                }
-            }
-        }
+/// The sticked tiles can move if none of them would move into a wall.
+fn can_boxes_move_in_dir(warehouse: &Warehouse, sticked_boxes: &[Point], dir: Direction) -> bool {
+    sticked_boxes.iter().all(|&pos| warehouse[pos + dir] != '#')
+}
 
-        Left => {
-            let fbox_left = sticked_boxes[0];
-            let next_left = fbox_left + DIRECTIONS[Left as usize];
-            if warehouse[next_left] == '#' {
-                return false;
-            }
-            if sticked_boxes.len() > 1 {
-                let fbox_right = sticked_boxes[1];
-                let next_right = fbox_right + DIRECTIONS[Left as usize];
-                if warehouse[next_right] == '#' {
-                    return false;
-                }
+fn sum_gps_coord(warehouse: &Warehouse, gps: Gps) -> usize {
+    warehouse
+        .iter()
+        .filter(|(_, &tile)| tile == 'O' || tile == '[')
+        .map(|((i, j), _)| {
+            let width = box_tiles(warehouse, Point::from_index((i, j))).len();
+            match gps {
+                Gps::LeftEdge => i * 100 + j,
+                Gps::Centre => i * 100 + j + (width - 1) / 2,
             }
-        }
+        })
+        .sum()
+}
 
-        Right => {
-            let fbox_left = sticked_boxes[0];
-            let next_left = fbox_left + DIRECTIONS[Right as usize];
-            if warehouse[next_left] == '#' {
-                return false;
-            }
-            if sticked_boxes.len() > 1 {
-                let fbox_right = sticked_boxes[1];
-                let next_right = fbox_right + DIRECTIONS[Right as usize];
-                if warehouse[next_right] == '#' {
-                    return false;
+/// Widens every tile `scale` times: a wall becomes `scale` walls, a box of width `w` becomes a
+/// box of width `w * scale`, and the robot keeps one tile followed by floor.
+///
+/// # Panics
+/// Panics if `scale` is 0.
+fn scale_tiles(warehouse: &Warehouse, scale: usize) -> Warehouse {
+    assert!(scale > 0, "cannot scale a warehouse to nothing");
+    let mut new_warehouse = Vec::new();
+    for row in warehouse.iter_rows() {
+        let mut new_row = Vec::new();
+        let mut col = 0;
+        while col < row.len() {
+            let width = if row[col] == '[' {
+                row[col..]
+                    .iter()
+                    .position(|&tile| tile == ']')
+                    .map_or(1, |end| end + 1)
+            } else {
+                1
+            };
+            match row[col] {
+                'O' | '[' => new_row.extend(box_of_width(width * scale)),
+                '@' => {
+                    new_row.push('@');
+                    new_row.extend(std::iter::repeat_n('.', scale - 1));
                 }
+                tile => new_row.extend(std::iter::repeat_n(tile, scale)),
             }
+            col += width;
         }
+
+        new_warehouse.push(new_row);
     }
 
-    true
+    Grid::from_rows(new_warehouse).expect("scaled rows have equal length")
 }
 
-fn sum_gps_coord(warehouse: &Warehouse) -> usize {
-    let mut sum = 0;
-    for (i, row) in warehouse.0.iter().enumerate() {
-        for (j, _) in row.iter().enumerate() {
-            let tile = warehouse[Pos(
-                i32::try_from(i).unwrap_or_default(),
-                i32::try_from(j).unwrap_or_default(),
-            )];
-            if tile == 'O' || tile == '[' {
-                sum += (i) * 100 + j;
-            }
-        }
-    }
-    sum
+fn solve(input: &str, config: Config) -> Option<u64> {
+    let mut document = parse_input(input)?;
+    document.warehouse = scale_tiles(&document.warehouse, config.scale);
+    let mut robot_pos = find_robot_pos(&document.warehouse);
+    move_robot(&mut document, &mut robot_pos);
+    Some(sum_gps_coord(&document.warehouse, config.gps) as u64)
 }
 
 #[must_use]
 pub fn part_one(input: &str) -> Option<u64> {
-    let mut document = parse_input(input);
-    let mut robot_pos = find_robot_pos(&document.warehouse);
-    move_robot(&mut document, &mut robot_pos);
-    Some(sum_gps_coord(&document.warehouse) as u64)
+    solve(input, PART_ONE)
 }
 
-fn double_tiles(warehouse: &Warehouse) -> Warehouse {
-    let mut new_warehouse = Vec::new();
-    for row in &warehouse.0 {
-        let mut new_row = Vec::new();
-        for &tile in row {
-            if tile == '#' {
-                new_row.push('#');
-                new_row.push('#');
-            } else if tile == 'O' {
-                new_row.push('[');
-                new_row.push(']');
-            } else if tile == '.' {
-                new_row.push('.');
-                new_row.push('.');
-            } else if tile == '@' {
-                new_row.push('@');
-                new_row.push('.');
-            }
+#[must_use]
+pub fn part_two(input: &str) -> Option<u64> {
+    solve(input, PART_TWO)
+}
+
+/// After the last part that runs, solves the variant given with `--scale` and `--gps` if it
+/// differs from the part's, then opens the `--playground` on the warehouse of that variant.
+fn show(input: &str, part: u8) {
+    if !advent_of_code::template::is_last_part(part) {
+        return;
+    }
+    let part_config = if part == 1 { PART_ONE } else { PART_TWO };
+    let config = part_config.with_args();
+    if config != part_config {
+        let gps = match config.gps {
+            Gps::LeftEdge => "left",
+            Gps::Centre => "centre",
+        };
+        match solve(input, config) {
+            Some(result) => println!("Scale {}, GPS {gps}: {result}", config.scale),
+            None => println!("Scale {}, GPS {gps}: ✖", config.scale),
         }
+    }
 
-        new_warehouse.push(new_row);
+    if std::env::args().any(|x| x == "--playground") {
+        let Some(document) = parse_input(input) else {
+            return;
+        };
+        play(
+            scale_tiles(&document.warehouse, config.scale),
+            document.moves,
+            config.gps,
+        );
     }
+}
 
-    Warehouse(new_warehouse)
+fn find_robot_pos(warehouse: &Warehouse) -> Point {
+    warehouse
+        .find(|&tile| tile == '@')
+        .map(Point::from_index)
+        .unwrap_or_default()
 }
 
-#[must_use]
-pub fn part_two(input: &str) -> Option<u64> {
-    let mut document = parse_input(input);
-    let new_warehouse = double_tiles(&document.warehouse);
-    document.warehouse = new_warehouse;
-    let mut robot_pos = find_robot_pos(&document.warehouse);
+/* -------------------------------------------------------------------------- */
+
+/// A command of the `--playground`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    /// Arrow keys, WASD or the arrows of the move list `^>v<`.
+    Move(Direction),
+    /// `u`
+    Undo,
+    /// `r`
+    Redo,
+    /// `n`, makes the next move of the puzzle's move list.
+    Replay,
+    /// `p`, makes all remaining moves of the move list.
+    ReplayAll,
+    /// `q`
+    Quit,
+}
 
-    move_robot(&mut document, &mut robot_pos);
-    Some(sum_gps_coord(&document.warehouse) as u64)
+impl Command {
+    /// Parses the keys typed on a line, skipping unknown ones.
+    fn parse_line(line: &str) -> Vec<Self> {
+        use Direction::{Down, Left, Right, Up};
+        let mut commands = Vec::new();
+        let mut keys = line.chars();
+        while let Some(key) = keys.next() {
+            let command = match key {
+                // arrow keys send `ESC [ A` to `ESC [ D`, or `ESC O A` to `ESC O D`
+                '\x1b' => match (keys.next(), keys.next()) {
+                    (Some('[' | 'O'), Some('A')) => Self::Move(Up),
+                    (Some('[' | 'O'), Some('B')) => Self::Move(Down),
+                    (Some('[' | 'O'), Some('C')) => Self::Move(Right),
+                    (Some('[' | 'O'), Some('D')) => Self::Move(Left),
+                    _ => continue,
+                },
+                'w' | '^' => Self::Move(Up),
+                'a' | '<' => Self::Move(Left),
+                's' | 'v' => Self::Move(Down),
+                'd' | '>' => Self::Move(Right),
+                'u' => Self::Undo,
+                'r' => Self::Redo,
+                'n' => Self::Replay,
+                'p' => Self::ReplayAll,
+                'q' => Self::Quit,
+                _ => continue,
+            };
+            commands.push(command);
+        }
+        commands
+    }
+}
+
+/// A move made in the playground: the robot's position and the changed tiles before and after.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    robot: (Point, Point),
+    tiles: Vec<(Point, char, char)>,
+    /// Whether the move was taken from the move list.
+    replayed: bool,
 }
 
-fn find_robot_pos(warehouse: &Warehouse) -> Pos {
-    let rows = warehouse.0.len();
-    let cols = warehouse.0[0].len();
-    for i in 0..rows {
-        for j in 0..cols {
-            let pos = Pos(
-                i32::try_from(i).unwrap_or_default(),
-                i32::try_from(j).unwrap_or_default(),
-            );
-            let tile = warehouse[pos];
-            if tile == '@' {
-                return pos;
+/// A warehouse explored by hand, with the history of moves for undo and redo.
+#[derive(Debug)]
+struct Playground {
+    warehouse: Warehouse,
+    robot: Point,
+    moves: Vec<Direction>,
+    /// How many moves of the move list were made.
+    replayed: usize,
+    undo: Vec<Step>,
+    redo: Vec<Step>,
+    gps: Gps,
+}
+
+impl Playground {
+    fn new(warehouse: Warehouse, moves: Vec<Direction>, gps: Gps) -> Self {
+        Self {
+            robot: find_robot_pos(&warehouse),
+            warehouse,
+            moves,
+            replayed: 0,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            gps,
+        }
+    }
+
+    /// Moves the robot by the rules of [`move_robot_in_dir`]. Bumping into a wall by hand is not
+    /// recorded, while every move of the move list is, so undo walks back through the list.
+    fn move_robot(&mut self, dir: Direction, replayed: bool) {
+        // only the pushed tiles and the ones they are pushed onto can change.
+        let mut changed = stick_robot_to_boxes2(&self.warehouse, self.robot, dir)
+            .into_iter()
+            .flat_map(|pos| [pos, pos + dir])
+            .collect::<Vec<_>>();
+        changed.sort_unstable();
+        changed.dedup();
+        let before = changed
+            .iter()
+            .map(|&pos| self.warehouse[pos])
+            .collect::<Vec<_>>();
+
+        let from = self.robot;
+        move_robot_in_dir(&mut self.warehouse, &mut self.robot, dir);
+        if from == self.robot && !replayed {
+            return;
+        }
+
+        let tiles = changed
+            .into_iter()
+            .zip(before)
+            .map(|(pos, before)| (pos, before, self.warehouse[pos]))
+            .collect();
+        self.undo.push(Step {
+            robot: (from, self.robot),
+            tiles,
+            replayed,
+        });
+        self.redo.clear();
+    }
+
+    /// Makes the next move of the move list. Returns `false` if all of them were made.
+    fn replay(&mut self) -> bool {
+        let Some(&dir) = self.moves.get(self.replayed) else {
+            return false;
+        };
+        self.replayed += 1;
+        self.move_robot(dir, true);
+        true
+    }
+
+    fn undo(&mut self) {
+        if let Some(step) = self.undo.pop() {
+            for &(pos, before, _) in &step.tiles {
+                self.warehouse[pos] = before;
+            }
+            self.robot = step.robot.0;
+            self.replayed -= usize::from(step.replayed);
+            self.redo.push(step);
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(step) = self.redo.pop() {
+            for &(pos, _, after) in &step.tiles {
+                self.warehouse[pos] = after;
+            }
+            self.robot = step.robot.1;
+            self.replayed += usize::from(step.replayed);
+            self.undo.push(step);
+        }
+    }
+
+    /// Runs the command, returns `false` to quit.
+    fn apply(&mut self, command: Command) -> bool {
+        match command {
+            Command::Move(dir) => self.move_robot(dir, false),
+            Command::Undo => self.undo(),
+            Command::Redo => self.redo(),
+            Command::Replay => {
+                self.replay();
             }
+            Command::ReplayAll => while self.replay() {},
+            Command::Quit => return false,
         }
+        true
     }
 
-    Pos(0, 0)
+    fn frame(&self) -> String {
+        render(&self.warehouse, |_, &tile| match tile {
+            '#' => Tile::new(tile).fg(Rgb::GREY),
+            _ if is_box(tile) => Tile::new(tile).fg(Rgb::YELLOW),
+            '@' => Tile::new(tile).fg(Rgb::GREEN),
+            _ => tile.into(),
+        })
+    }
+
+    fn status(&self) -> String {
+        let next = self
+            .moves
+            .get(self.replayed)
+            .map_or_else(String::new, |dir| format!(", next {dir}"));
+        format!(
+            "GPS sum {} · {}/{} moves replayed{next} · {} to undo, {} to redo\n\
+             [arrows/wasd] move, [u] undo, [r] redo, [n] next move, [p] all moves, [q] quit, \
+             then [enter]",
+            sum_gps_coord(&self.warehouse, self.gps),
+            self.replayed,
+            self.moves.len(),
+            self.undo.len(),
+            self.redo.len(),
+        )
+    }
+}
+
+/// Explores the warehouse by hand with the commands typed on stdin, until `q` or the end of input.
+fn play(warehouse: Warehouse, moves: Vec<Direction>, gps: Gps) {
+    let mut playground = Playground::new(warehouse, moves, gps);
+    let mut stdout = io::stdout().lock();
+    let stdin = io::stdin();
+    let mut line = String::new();
+    loop {
+        let _ = write!(
+            stdout,
+            "{ANSI_CLEAR}{}\n{}\n",
+            playground.frame(),
+            playground.status()
+        );
+        let _ = stdout.flush();
+
+        line.clear();
+        if !matches!(stdin.read_line(&mut line), Ok(1..)) {
+            break;
+        }
+        if !Command::parse_line(&line)
+            .into_iter()
+            .all(|command| playground.apply(command))
+        {
+            break;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -359,16 +531,150 @@ mod tests {
         assert_eq!(result, Some(9021));
     }
 
+    #[test]
+    fn test_scale_tiles() {
+        let warehouse = "#O.@\n#[]O".parse::<Warehouse>().unwrap();
+        assert_eq!(
+            scale_tiles(&warehouse, 3).to_string(),
+            "###[=]...@..\n###[====][=]"
+        );
+        assert_eq!(scale_tiles(&warehouse, 1), warehouse);
+    }
+
+    #[test]
+    fn test_mixed_box_widths() {
+        use Direction::*;
+        let input = r"#########
+#.......#
+#.[==]..#
+#..O[]..#
+#...@...#
+#########";
+        let mut document = parse_input(input).unwrap();
+        let mut robot_pos = find_robot_pos(&document.warehouse);
+        let sticked = stick_robot_to_boxes2(&document.warehouse, robot_pos, Up);
+        let expected = vec![
+            Point::new(2, 5),
+            Point::new(2, 2),
+            Point::new(2, 3),
+            Point::new(2, 4),
+            Point::new(3, 5),
+            Point::new(3, 4),
+            Point::new(4, 4),
+        ];
+        assert_eq!(sticked, expected);
+
+        move_robot_in_dir(&mut document.warehouse, &mut robot_pos, Up);
+        let expect = r"#########
+#.[==]..#
+#...[]..#
+#..O@...#
+#.......#
+#########";
+        assert_eq!(document.warehouse.to_string(), expect);
+        assert_eq!(
+            sum_gps_coord(&document.warehouse, Gps::LeftEdge),
+            102 + 204 + 303
+        );
+        assert_eq!(
+            sum_gps_coord(&document.warehouse, Gps::Centre),
+            103 + 204 + 303
+        );
+
+        // the wide box is against the wall now
+        move_robot_in_dir(&mut document.warehouse, &mut robot_pos, Up);
+        assert_eq!(document.warehouse.to_string(), expect);
+        move_robot_in_dir(&mut document.warehouse, &mut robot_pos, Left);
+        assert_eq!(
+            document.warehouse.row(3),
+            "#.O@....#".chars().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        use Direction::*;
+        assert_eq!(
+            Command::parse_line("wD\x1b[B\x1bOD<x unpq\n"),
+            [
+                Command::Move(Up),
+                Command::Move(Down),
+                Command::Move(Left),
+                Command::Move(Left),
+                Command::Undo,
+                Command::Replay,
+                Command::ReplayAll,
+                Command::Quit,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_playground() {
+        use Direction::*;
+        let document = parse_input(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let mut playground = Playground::new(
+            scale_tiles(&document.warehouse, 2),
+            document.moves,
+            Gps::LeftEdge,
+        );
+        let start = playground.warehouse.clone();
+
+        // pushing the box to the left, then bumping into the wall
+        for command in Command::parse_line("aaas") {
+            assert!(playground.apply(command));
+        }
+        assert_eq!(playground.robot, Point::new(4, 5));
+        assert_eq!(
+            &playground.warehouse.row(4)[2..7],
+            ['.', '[', ']', '@', '.']
+        );
+        assert_eq!(playground.undo.len(), 3);
+
+        playground.apply(Command::Undo);
+        playground.apply(Command::Undo);
+        assert_eq!(playground.robot, Point::new(4, 7));
+        assert_eq!(playground.redo.len(), 2);
+        playground.apply(Command::Undo);
+        assert_eq!(playground.warehouse, start);
+        playground.apply(Command::Redo);
+        assert_eq!(playground.robot, Point::new(4, 7));
+        assert_eq!(&playground.warehouse.row(4)[5..9], ['[', ']', '@', '.']);
+
+        // a new move drops the redo history, replaying continues from there
+        playground.apply(Command::Move(Right));
+        assert!(playground.redo.is_empty());
+        playground.apply(Command::Undo);
+        playground.apply(Command::Undo);
+        assert_eq!(playground.warehouse, start);
+        playground.apply(Command::ReplayAll);
+        assert_eq!(playground.replayed, 700);
+        assert_eq!(sum_gps_coord(&playground.warehouse, Gps::LeftEdge), 9021);
+
+        // undoing a replayed move also rewinds the move list
+        playground.apply(Command::Undo);
+        assert_eq!(playground.replayed, 699);
+        playground.apply(Command::Replay);
+        assert_eq!(sum_gps_coord(&playground.warehouse, Gps::LeftEdge), 9021);
+        assert!(!playground.apply(Command::Quit));
+    }
+
     #[test]
     fn test_stick_boxes_left() {
         use Direction::*;
         let input = r"########
 ........
 ..[][]@.";
-        let document = parse_input(input);
+        let document = parse_input(input).unwrap();
         let robot_pos = find_robot_pos(&document.warehouse);
         let sticked = stick_robot_to_boxes2(&document.warehouse, robot_pos, Left);
-        let expected = vec![Pos(2, 2), Pos(2, 3), Pos(2, 4), Pos(2, 5), Pos(2, 6)];
+        let expected = vec![
+            Point::new(2, 2),
+            Point::new(2, 3),
+            Point::new(2, 4),
+            Point::new(2, 5),
+            Point::new(2, 6),
+        ];
         assert_eq!(sticked, expected);
     }
 
@@ -378,10 +684,16 @@ mod tests {
         let input = r"########
 ........
 ..[][]@.";
-        let document = parse_input(input);
+        let document = parse_input(input).unwrap();
         let robot_pos = find_robot_pos(&document.warehouse);
         let sticked = stick_robot_to_boxes2(&document.warehouse, robot_pos, Left);
-        let expected = vec![Pos(2, 2), Pos(2, 3), Pos(2, 4), Pos(2, 5), Pos(2, 6)];
+        let expected = vec![
+            Point::new(2, 2),
+            Point::new(2, 3),
+            Point::new(2, 4),
+            Point::new(2, 5),
+            Point::new(2, 6),
+        ];
         assert_eq!(sticked, expected);
         assert!(can_boxes_move_in_dir(&document.warehouse, &sticked, Left));
     }
@@ -392,10 +704,16 @@ mod tests {
         let input = r"########
 ........
 .#[][]@.";
-        let document = parse_input(input);
+        let document = parse_input(input).unwrap();
         let robot_pos = find_robot_pos(&document.warehouse);
         let sticked = stick_robot_to_boxes2(&document.warehouse, robot_pos, Left);
-        let expected = vec![Pos(2, 2), Pos(2, 3), Pos(2, 4), Pos(2, 5), Pos(2, 6)];
+        let expected = vec![
+            Point::new(2, 2),
+            Point::new(2, 3),
+            Point::new(2, 4),
+            Point::new(2, 5),
+            Point::new(2, 6),
+        ];
         assert_eq!(sticked, expected);
         assert!(!can_boxes_move_in_dir(&document.warehouse, &sticked, Left));
     }
@@ -406,10 +724,16 @@ mod tests {
         let input = r"########
 ........
 .@[][]..";
-        let document = parse_input(input);
+        let document = parse_input(input).unwrap();
         let robot_pos = find_robot_pos(&document.warehouse);
         let sticked = stick_robot_to_boxes2(&document.warehouse, robot_pos, Right);
-        let expected = vec![Pos(2, 5), Pos(2, 4), Pos(2, 3), Pos(2, 2), Pos(2, 1)];
+        let expected = vec![
+            Point::new(2, 5),
+            Point::new(2, 4),
+            Point::new(2, 3),
+            Point::new(2, 2),
+            Point::new(2, 1),
+        ];
         assert_eq!(sticked, expected);
     }
 
@@ -419,10 +743,16 @@ mod tests {
         let input = r"########
 ........
 .@[][]..";
-        let document = parse_input(input);
+        let document = parse_input(input).unwrap();
         let robot_pos = find_robot_pos(&document.warehouse);
         let sticked = stick_robot_to_boxes2(&document.warehouse, robot_pos, Right);
-        let expected = vec![Pos(2, 5), Pos(2, 4), Pos(2, 3), Pos(2, 2), Pos(2, 1)];
+        let expected = vec![
+            Point::new(2, 5),
+            Point::new(2, 4),
+            Point::new(2, 3),
+            Point::new(2, 2),
+            Point::new(2, 1),
+        ];
         assert_eq!(sticked, expected);
         assert!(can_boxes_move_in_dir(&document.warehouse, &sticked, Right));
     }
@@ -432,10 +762,16 @@ mod tests {
         let input = r"########
 ........
 .@[][]#.";
-        let document = parse_input(input);
+        let document = parse_input(input).unwrap();
         let robot_pos = find_robot_pos(&document.warehouse);
         let sticked = stick_robot_to_boxes2(&document.warehouse, robot_pos, Right);
-        let expected = vec![Pos(2, 5), Pos(2, 4), Pos(2, 3), Pos(2, 2), Pos(2, 1)];
+        let expected = vec![
+            Point::new(2, 5),
+            Point::new(2, 4),
+            Point::new(2, 3),
+            Point::new(2, 2),
+            Point::new(2, 1),
+        ];
         assert_eq!(sticked, expected);
         assert!(!can_boxes_move_in_dir(&document.warehouse, &sticked, Right));
     }
@@ -448,19 +784,19 @@ mod tests {
 ...[][].
 ....[]..
 .....@..";
-        let document = parse_input(input);
+        let document = parse_input(input).unwrap();
         let robot_pos = find_robot_pos(&document.warehouse);
         let sticked = stick_robot_to_boxes2(&document.warehouse, robot_pos, Up);
         let expected = vec![
-            Pos(2, 3),
-            Pos(2, 4),
-            Pos(3, 3),
-            Pos(3, 4),
-            Pos(3, 6),
-            Pos(3, 5),
-            Pos(4, 4),
-            Pos(4, 5),
-            Pos(5, 5),
+            Point::new(2, 3),
+            Point::new(2, 4),
+            Point::new(3, 3),
+            Point::new(3, 4),
+            Point::new(3, 6),
+            Point::new(3, 5),
+            Point::new(4, 4),
+            Point::new(4, 5),
+            Point::new(5, 5),
         ];
         assert_eq!(sticked, expected);
     }
@@ -474,19 +810,19 @@ mod tests {
 ...[][].
 ....[]..
 .....@..";
-        let mut document = parse_input(input);
+        let mut document = parse_input(input).unwrap();
         let mut robot_pos = find_robot_pos(&document.warehouse);
         let sticked = stick_robot_to_boxes2(&document.warehouse, robot_pos, Up);
         let expected = vec![
-            Pos(2, 3),
-            Pos(2, 4),
-            Pos(3, 3),
-            Pos(3, 4),
-            Pos(3, 6),
-            Pos(3, 5),
-            Pos(4, 4),
-            Pos(4, 5),
-            Pos(5, 5),
+            Point::new(2, 3),
+            Point::new(2, 4),
+            Point::new(3, 3),
+            Point::new(3, 4),
+            Point::new(3, 6),
+            Point::new(3, 5),
+            Point::new(4, 4),
+            Point::new(4, 5),
+            Point::new(5, 5),
         ];
         assert_eq!(sticked, expected);
         assert!(can_boxes_move_in_dir(&document.warehouse, &sticked, Up));
@@ -496,12 +832,11 @@ mod tests {
 ....[]..
 .....@..
 ........"
-            .lines()
-            .map(|line| line.chars().collect::<Vec<_>>())
-            .collect::<Vec<_>>();
+            .parse::<Warehouse>()
+            .unwrap();
         move_robot_in_dir(&mut document.warehouse, &mut robot_pos, Up);
 
-        assert_eq!(Warehouse(expect), document.warehouse);
+        assert_eq!(expect, document.warehouse);
     }
 
     #[test]
@@ -522,19 +857,19 @@ mod tests {
 .....@..",
         ];
         for input in inputs {
-            let document = parse_input(input);
+            let document = parse_input(input).unwrap();
             let robot_pos = find_robot_pos(&document.warehouse);
             let sticked = stick_robot_to_boxes2(&document.warehouse, robot_pos, Up);
             let expected = vec![
-                Pos(2, 3),
-                Pos(2, 4),
-                Pos(3, 3),
-                Pos(3, 4),
-                Pos(3, 6),
-                Pos(3, 5),
-                Pos(4, 4),
-                Pos(4, 5),
-                Pos(5, 5),
+                Point::new(2, 3),
+                Point::new(2, 4),
+                Point::new(3, 3),
+                Point::new(3, 4),
+                Point::new(3, 6),
+                Point::new(3, 5),
+                Point::new(4, 4),
+                Point::new(4, 5),
+                Point::new(5, 5),
             ];
             assert_eq!(sticked, expected);
             assert!(!can_boxes_move_in_dir(&document.warehouse, &sticked, Up));
@@ -550,17 +885,17 @@ mod tests {
 ...[][].
 ....[]..
 ........";
-        let document = parse_input(input);
+        let document = parse_input(input).unwrap();
         let robot_pos = find_robot_pos(&document.warehouse);
         let sticked = stick_robot_to_boxes2(&document.warehouse, robot_pos, Down);
         let expected = vec![
-            Pos(4, 5),
-            Pos(4, 4),
-            Pos(3, 4),
-            Pos(3, 3),
-            Pos(2, 4),
-            Pos(2, 3),
-            Pos(1, 3),
+            Point::new(4, 5),
+            Point::new(4, 4),
+            Point::new(3, 4),
+            Point::new(3, 3),
+            Point::new(2, 4),
+            Point::new(2, 3),
+            Point::new(1, 3),
         ];
         assert_eq!(sticked, expected);
     }
@@ -574,17 +909,17 @@ mod tests {
 ...[][].
 ....[]..
 ........";
-        let mut document = parse_input(input);
+        let mut document = parse_input(input).unwrap();
         let mut robot_pos = find_robot_pos(&document.warehouse);
         let sticked = stick_robot_to_boxes2(&document.warehouse, robot_pos, Down);
         let expected = vec![
-            Pos(4, 5),
-            Pos(4, 4),
-            Pos(3, 4),
-            Pos(3, 3),
-            Pos(2, 4),
-            Pos(2, 3),
-            Pos(1, 3),
+            Point::new(4, 5),
+            Point::new(4, 4),
+            Point::new(3, 4),
+            Point::new(3, 3),
+            Point::new(2, 4),
+            Point::new(2, 3),
+            Point::new(1, 3),
         ];
         assert_eq!(sticked, expected);
         assert!(can_boxes_move_in_dir(&document.warehouse, &sticked, Down));
@@ -594,12 +929,11 @@ mod tests {
 ...[][].
 ...[]...
 ....[].."
-            .lines()
-            .map(|line| line.chars().collect::<Vec<_>>())
-            .collect::<Vec<_>>();
+            .parse::<Warehouse>()
+            .unwrap();
 
         move_robot_in_dir(&mut document.warehouse, &mut robot_pos, Down);
-        assert_eq!(Warehouse(expect), document.warehouse);
+        assert_eq!(expect, document.warehouse);
     }
 
     #[test]
@@ -620,17 +954,17 @@ mod tests {
 ........",
         ];
         for input in inputs {
-            let document = parse_input(input);
+            let document = parse_input(input).unwrap();
             let robot_pos = find_robot_pos(&document.warehouse);
             let sticked = stick_robot_to_boxes2(&document.warehouse, robot_pos, Down);
             let expected = vec![
-                Pos(4, 5),
-                Pos(4, 4),
-                Pos(3, 4),
-                Pos(3, 3),
-                Pos(2, 4),
-                Pos(2, 3),
-                Pos(1, 3),
+                Point::new(4, 5),
+                Point::new(4, 4),
+                Point::new(3, 4),
+                Point::new(3, 3),
+                Point::new(2, 4),
+                Point::new(2, 3),
+                Point::new(1, 3),
             ];
             assert_eq!(sticked, expected);
             assert!(!can_boxes_move_in_dir(&document.warehouse, &sticked, Down));
@@ -0,0 +1,307 @@
+use crate::grid::Grid;
+
+/// A disjoint-set forest over the elements `0..len`, with union by size and path halving.
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    sets: usize,
+}
+
+impl UnionFind {
+    /// Creates `len` singleton sets.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+            sets: len,
+        }
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// The number of disjoint sets.
+    pub const fn sets(&self) -> usize {
+        self.sets
+    }
+
+    /// The representative of the set containing `x`.
+    ///
+    /// # Panics
+    /// Panics if `x` is out of bounds.
+    pub fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    /// Merges the sets containing `a` and `b`. Returns `false` if they already were the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            (a, b) = (b, a);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.sets -= 1;
+        true
+    }
+
+    /// Returns `true` if `a` and `b` are in the same set.
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// The number of elements in the set containing `x`.
+    pub fn set_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Collects all cells reachable from `start` through orthogonal steps between `connected` cells,
+/// without recursion. The cells are returned in row-major order, `start` included.
+pub fn flood_fill<T>(
+    grid: &Grid<T>,
+    start: (usize, usize),
+    mut connected: impl FnMut(&T, &T) -> bool,
+) -> Vec<(usize, usize)> {
+    let mut seen = Grid::new(grid.rows(), grid.cols(), false);
+    seen[start] = true;
+    let mut stack = vec![start];
+    let mut cells = Vec::new();
+    while let Some(pos) = stack.pop() {
+        cells.push(pos);
+        for next in grid.neighbours4(pos) {
+            if !seen[next] && connected(&grid[pos], &grid[next]) {
+                seen[next] = true;
+                stack.push(next);
+            }
+        }
+    }
+
+    cells.sort_unstable();
+    cells
+}
+
+/// Labels the connected components of the grid, where orthogonal neighbours belong to the same
+/// component if `connected` holds for them.
+///
+/// Returns a grid of labels and the number of components. Labels are numbered from 0 in the
+/// row-major order of each component's first cell.
+pub fn label<T>(grid: &Grid<T>, mut connected: impl FnMut(&T, &T) -> bool) -> (Grid<usize>, usize) {
+    let cols = grid.cols();
+    let index = |(row, col): (usize, usize)| row * cols + col;
+
+    let mut sets = UnionFind::new(grid.rows() * cols);
+    for (pos, cell) in grid.iter() {
+        let (row, col) = pos;
+        for next in [(row + 1, col), (row, col + 1)] {
+            if grid.get(next).is_some_and(|other| connected(cell, other)) {
+                sets.union(index(pos), index(next));
+            }
+        }
+    }
+
+    let mut labels_of_roots = vec![usize::MAX; sets.len()];
+    let mut count = 0;
+    let mut labels = Grid::new(grid.rows(), cols, 0);
+    for pos in grid.positions() {
+        let root = sets.find(index(pos));
+        if labels_of_roots[root] == usize::MAX {
+            labels_of_roots[root] = count;
+            count += 1;
+        }
+        labels[pos] = labels_of_roots[root];
+    }
+
+    (labels, count)
+}
+
+/// The inclusive range of rows and columns a region spans.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub min: (usize, usize),
+    pub max: (usize, usize),
+}
+
+impl BoundingBox {
+    pub const fn rows(&self) -> usize {
+        self.max.0 - self.min.0 + 1
+    }
+
+    pub const fn cols(&self) -> usize {
+        self.max.1 - self.min.1 + 1
+    }
+
+    fn extend(&mut self, (row, col): (usize, usize)) {
+        self.min = (self.min.0.min(row), self.min.1.min(col));
+        self.max = (self.max.0.max(row), self.max.1.max(col));
+    }
+}
+
+/// A connected component of a grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    /// The cells in row-major order.
+    pub cells: Vec<(usize, usize)>,
+    /// The number of cell edges between the region and other cells or the outside.
+    pub perimeter: usize,
+    /// The number of straight sides of the fence around the region, holes included.
+    pub sides: usize,
+    pub bounds: BoundingBox,
+}
+
+impl Region {
+    /// The number of cells.
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+/// Splits the grid into regions of orthogonally adjacent, equal cells.
+pub fn regions<T: PartialEq>(grid: &Grid<T>) -> Vec<Region> {
+    regions_by(grid, PartialEq::eq)
+}
+
+/// Splits the grid into regions of orthogonally adjacent cells for which `connected` holds.
+///
+/// Regions are ordered by their first cell in row-major order, like the labels of [`label`].
+pub fn regions_by<T>(grid: &Grid<T>, connected: impl FnMut(&T, &T) -> bool) -> Vec<Region> {
+    let (labels, count) = label(grid, connected);
+    let mut regions: Vec<Option<Region>> = vec![None; count];
+
+    for (pos, &id) in labels.iter() {
+        let same = |offset| {
+            labels
+                .offset(pos, offset)
+                .is_some_and(|next| labels[next] == id)
+        };
+        let region = regions[id].get_or_insert_with(|| Region {
+            cells: Vec::new(),
+            perimeter: 0,
+            sides: 0,
+            bounds: BoundingBox { min: pos, max: pos },
+        });
+        region.cells.push(pos);
+        region.bounds.extend(pos);
+        region.perimeter += [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .into_iter()
+            .filter(|&offset| !same(offset))
+            .count();
+        // a polygon has as many sides as corners. A cell is at an outer corner if both orthogonal
+        // neighbours towards it are outside, and at an inner one if only the diagonal one is.
+        region.sides += [(-1, -1), (-1, 1), (1, -1), (1, 1)]
+            .into_iter()
+            .filter(|&(dr, dc)| {
+                let (vertical, horizontal) = (same((dr, 0)), same((0, dc)));
+                (!vertical && !horizontal) || (vertical && horizontal && !same((dr, dc)))
+            })
+            .count();
+    }
+
+    regions.into_iter().flatten().collect()
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{flood_fill, label, regions, regions_by, BoundingBox, UnionFind};
+    use crate::grid::Grid;
+
+    #[test]
+    fn union_find() {
+        let mut sets = UnionFind::new(5);
+        assert_eq!(sets.sets(), 5);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(3, 4));
+        assert!(sets.union(1, 4));
+        assert!(!sets.union(0, 3));
+        assert!(sets.same(0, 4));
+        assert!(!sets.same(0, 2));
+        assert_eq!(sets.set_size(3), 4);
+        assert_eq!(sets.sets(), 2);
+    }
+
+    #[test]
+    fn flood_fill_matches_labels() {
+        let grid: Grid<char> = "AAB\nABB\nCCB".parse().unwrap();
+        let (labels, count) = label(&grid, PartialEq::eq);
+        assert_eq!(count, 3);
+        assert_eq!(labels.to_string(), "001\n011\n221");
+        assert_eq!(
+            flood_fill(&grid, (1, 1), PartialEq::eq),
+            [(0, 2), (1, 1), (1, 2), (2, 2)]
+        );
+    }
+
+    #[test]
+    fn measures_regions() {
+        let grid: Grid<char> = "AAAA\nBBCD\nBBCC\nEEEC".parse().unwrap();
+        let regions = regions(&grid);
+        let measures = regions
+            .iter()
+            .map(|region| {
+                (
+                    grid[region.cells[0]],
+                    region.area(),
+                    region.perimeter,
+                    region.sides,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            measures,
+            [
+                ('A', 4, 10, 4),
+                ('B', 4, 8, 4),
+                ('C', 4, 10, 8),
+                ('D', 1, 4, 4),
+                ('E', 3, 8, 4)
+            ]
+        );
+        assert_eq!(
+            regions[2].bounds,
+            BoundingBox {
+                min: (1, 2),
+                max: (3, 3)
+            }
+        );
+        assert_eq!((regions[2].bounds.rows(), regions[2].bounds.cols()), (3, 2));
+    }
+
+    #[test]
+    fn counts_sides_of_holes_and_diagonals() {
+        // the two B regions touch the outer A region only diagonally
+        let grid: Grid<char> = "AAAAAA\nAAABBA\nAAABBA\nABBAAA\nABBAAA\nAAAAAA"
+            .parse()
+            .unwrap();
+        let regions = regions(&grid);
+        assert_eq!(regions.len(), 3);
+        assert_eq!((regions[0].area(), regions[0].sides), (28, 12));
+        assert_eq!((regions[1].area(), regions[1].sides), (4, 4));
+
+        // large regions do not overflow the stack
+        let grid = Grid::new(300, 300, 0u8);
+        let regions = regions_by(&grid, |a, b| a == b);
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].perimeter, 1200);
+        assert_eq!(regions[0].sides, 4);
+        assert_eq!(flood_fill(&grid, (150, 150), |_, _| true).len(), 90_000);
+    }
+}
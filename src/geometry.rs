@@ -0,0 +1,292 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A point on a 2D grid, `row` grows downwards and `col` grows to the right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point {
+    pub row: i64,
+    pub col: i64,
+}
+
+impl Point {
+    pub const ORIGIN: Self = Self::new(0, 0);
+
+    pub const fn new(row: i64, col: i64) -> Self {
+        Self { row, col }
+    }
+
+    /// Creates a point from grid indices.
+    ///
+    /// # Panics
+    /// Panics if an index does not fit into an `i64`.
+    pub fn from_index((row, col): (usize, usize)) -> Self {
+        Self::new(
+            i64::try_from(row).expect("row index overflows i64"),
+            i64::try_from(col).expect("column index overflows i64"),
+        )
+    }
+
+    /// Converts the point into grid indices, returns [`None`] if a coordinate is negative.
+    pub fn to_index(self) -> Option<(usize, usize)> {
+        Some((
+            usize::try_from(self.row).ok()?,
+            usize::try_from(self.col).ok()?,
+        ))
+    }
+
+    /// The Manhattan (taxicab) distance between two points.
+    pub const fn manhattan(self, other: Self) -> u64 {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+
+    /// The 4 orthogonal neighbours, in the order of [`Direction::ALL`].
+    pub fn neighbours4(self) -> [Self; 4] {
+        Direction::ALL.map(|dir| self + dir)
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.row + other.row, self.col + other.col)
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.row - other.row, self.col - other.col)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Self;
+
+    fn mul(self, factor: i64) -> Self {
+        Self::new(self.row * factor, self.col * factor)
+    }
+}
+
+impl Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.row, -self.col)
+    }
+}
+
+impl Add<Direction> for Point {
+    type Output = Self;
+
+    fn add(self, dir: Direction) -> Self {
+        self + dir.unit()
+    }
+}
+
+impl AddAssign<Direction> for Point {
+    fn add_assign(&mut self, dir: Direction) {
+        *self = *self + dir;
+    }
+}
+
+impl Display for Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.row, self.col)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// One of the 4 orthogonal directions on a grid, declared in clockwise order.
+///
+/// # Display
+/// A direction displays as its arrow symbol.
+///
+/// ```
+/// # use advent_of_code::geometry::Direction;
+/// let dir = Direction::try_from('^').unwrap();
+/// assert_eq!(dir.turn_right().to_string(), ">")
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// All directions in clockwise order, starting with [`Direction::Up`].
+    pub const ALL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    const fn from_index(idx: u8) -> Self {
+        Self::ALL[(idx % 4) as usize]
+    }
+
+    /// Rotates by 90 degrees clockwise.
+    #[must_use]
+    pub const fn turn_right(self) -> Self {
+        Self::from_index(self as u8 + 1)
+    }
+
+    /// Rotates by 90 degrees counter-clockwise.
+    #[must_use]
+    pub const fn turn_left(self) -> Self {
+        Self::from_index(self as u8 + 3)
+    }
+
+    /// Rotates by 180 degrees.
+    #[must_use]
+    pub const fn reverse(self) -> Self {
+        Self::from_index(self as u8 + 2)
+    }
+
+    /// The minimum number of 90 degree turns needed to face `other`, either 0, 1 or 2.
+    pub const fn turns_to(self, other: Self) -> u8 {
+        let clockwise = (other as u8 + 4 - self as u8) % 4;
+        if clockwise > 2 {
+            4 - clockwise
+        } else {
+            clockwise
+        }
+    }
+
+    /// The offset of a single step in this direction.
+    pub const fn unit(self) -> Point {
+        match self {
+            Self::Up => Point::new(-1, 0),
+            Self::Right => Point::new(0, 1),
+            Self::Down => Point::new(1, 0),
+            Self::Left => Point::new(0, -1),
+        }
+    }
+
+    /// The arrow symbol, one of `^>v<`.
+    pub const fn arrow(self) -> char {
+        match self {
+            Self::Up => '^',
+            Self::Right => '>',
+            Self::Down => 'v',
+            Self::Left => '<',
+        }
+    }
+
+    /// Returns `true` for [`Direction::Left`] and [`Direction::Right`].
+    pub const fn is_horizontal(self) -> bool {
+        matches!(self, Self::Left | Self::Right)
+    }
+}
+
+impl From<Direction> for Point {
+    fn from(dir: Direction) -> Self {
+        dir.unit()
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.arrow())
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = DirectionFromCharError;
+
+    /// Parses an arrow (`^>v<`) or a compass point (`NESW`).
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '^' | 'N' => Ok(Self::Up),
+            '>' | 'E' => Ok(Self::Right),
+            'v' | 'S' => Ok(Self::Down),
+            '<' | 'W' => Ok(Self::Left),
+            _ => Err(DirectionFromCharError(c)),
+        }
+    }
+}
+
+/// An error which can be returned when parsing a [`Direction`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct DirectionFromCharError(pub char);
+
+impl Error for DirectionFromCharError {}
+
+impl Display for DirectionFromCharError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid direction {:?}, expecting one of `^>v<` or `NESW`",
+            self.0
+        )
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{Direction, Point};
+    use Direction::{Down, Left, Right, Up};
+
+    #[test]
+    fn point_arithmetic() {
+        let p = Point::new(2, 3);
+        assert_eq!(p + Point::new(1, -1), Point::new(3, 2));
+        assert_eq!(p - Point::new(1, -1), Point::new(1, 4));
+        assert_eq!(p * 3, Point::new(6, 9));
+        assert_eq!(-p, Point::new(-2, -3));
+        assert_eq!(p + Up, Point::new(1, 3));
+        assert_eq!(p.manhattan(Point::new(-1, 5)), 5);
+    }
+
+    #[test]
+    fn point_indices() {
+        assert_eq!(Point::new(2, 3).to_index(), Some((2, 3)));
+        assert_eq!(Point::new(-1, 3).to_index(), None);
+        assert_eq!(Point::new(1, -3).to_index(), None);
+        assert_eq!(Point::from_index((4, 5)), Point::new(4, 5));
+    }
+
+    #[test]
+    fn turns() {
+        assert_eq!(Up.turn_right(), Right);
+        assert_eq!(Left.turn_right(), Up);
+        assert_eq!(Up.turn_left(), Left);
+        assert_eq!(Right.reverse(), Left);
+        for dir in Direction::ALL {
+            assert_eq!(dir.turn_left().turn_right(), dir);
+            assert_eq!(dir.turns_to(dir), 0);
+            assert_eq!(dir.turns_to(dir.turn_left()), 1);
+            assert_eq!(dir.turns_to(dir.turn_right()), 1);
+            assert_eq!(dir.turns_to(dir.reverse()), 2);
+            assert_eq!(dir.unit() + dir.reverse().unit(), Point::ORIGIN);
+        }
+    }
+
+    #[test]
+    fn parses_directions() {
+        assert_eq!(Direction::try_from('^'), Ok(Up));
+        assert_eq!(Direction::try_from('v'), Ok(Down));
+        assert_eq!(Direction::try_from('<'), Ok(Left));
+        assert_eq!(Direction::try_from('E'), Ok(Right));
+        assert_eq!(Direction::try_from('S'), Ok(Down));
+        assert!(Direction::try_from('x').is_err());
+        for dir in Direction::ALL {
+            assert_eq!(Direction::try_from(dir.arrow()), Ok(dir));
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+
+/// A directed graph, as the set of successors of each node.
+#[derive(Debug, Clone)]
+pub struct Graph<N> {
+    edges: HashMap<N, HashSet<N>>,
+}
+
+impl<N> Default for Graph<N> {
+    fn default() -> Self {
+        Self {
+            edges: HashMap::new(),
+        }
+    }
+}
+
+impl<N: Clone + Eq + Hash> Graph<N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a node without any edges, if it is not in the graph yet.
+    pub fn add_node(&mut self, node: N) {
+        self.edges.entry(node).or_default();
+    }
+
+    /// Adds an edge from `from` to `to`, and both nodes if they are not in the graph yet.
+    pub fn add_edge(&mut self, from: N, to: N) {
+        self.add_node(to.clone());
+        self.edges.entry(from).or_default().insert(to);
+    }
+
+    pub fn contains_node(&self, node: &N) -> bool {
+        self.edges.contains_key(node)
+    }
+
+    pub fn contains_edge(&self, from: &N, to: &N) -> bool {
+        self.edges.get(from).is_some_and(|next| next.contains(to))
+    }
+
+    /// The nodes in no particular order.
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.edges.keys()
+    }
+
+    /// The nodes an edge from `node` leads to, in no particular order. A node that is not in the
+    /// graph has none.
+    pub fn successors(&self, node: &N) -> impl Iterator<Item = &N> {
+        self.edges.get(node).into_iter().flatten()
+    }
+
+    /// Sorts all nodes so that every edge leads from an earlier node to a later one.
+    ///
+    /// # Errors
+    /// Returns a [`Cycle`] if the edges contradict each other.
+    pub fn toposort(&self) -> Result<Vec<N>, Cycle<N>> {
+        let nodes = self.edges.keys().cloned().collect::<Vec<_>>();
+        self.toposort_subset(&nodes)
+    }
+
+    /// Sorts `nodes` so that every edge between two of them leads from the earlier node to the
+    /// later one. Edges to or from other nodes are ignored, and nodes that are not in the graph
+    /// have no edges. Repeated nodes are only kept once.
+    ///
+    /// Of all valid orders, this is the one closest to the given order: a node is only moved
+    /// behind the nodes an edge forces it behind, so nodes already in order stay as they are.
+    ///
+    /// ```
+    /// # use advent_of_code::graph::Graph;
+    /// let graph = Graph::from_iter([(1, 2), (2, 3), (3, 4)]);
+    /// assert_eq!(graph.toposort_subset(&[4, 3, 9]), Ok(vec![3, 4, 9]));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns a [`Cycle`] if the edges between the nodes contradict each other.
+    pub fn toposort_subset(&self, nodes: &[N]) -> Result<Vec<N>, Cycle<N>> {
+        // Kahn's algorithm over the indices of the first occurrences.
+        let mut index = HashMap::new();
+        let mut unique = Vec::new();
+        for node in nodes {
+            index.entry(node).or_insert_with(|| {
+                unique.push(node);
+                unique.len() - 1
+            });
+        }
+        let successors = unique
+            .iter()
+            .map(|node| {
+                self.successors(node)
+                    .filter_map(|next| index.get(next).copied())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut in_degree = vec![0; unique.len()];
+        for &next in successors.iter().flatten() {
+            in_degree[next] += 1;
+        }
+
+        // the earliest node with no predecessors left goes next.
+        let mut ready = (0..unique.len())
+            .filter(|&idx| in_degree[idx] == 0)
+            .map(Reverse)
+            .collect::<BinaryHeap<_>>();
+        let mut sorted = Vec::with_capacity(unique.len());
+        while let Some(Reverse(idx)) = ready.pop() {
+            sorted.push(unique[idx].clone());
+            for &next in &successors[idx] {
+                in_degree[next] -= 1;
+                if in_degree[next] == 0 {
+                    ready.push(Reverse(next));
+                }
+            }
+        }
+
+        if sorted.len() == unique.len() {
+            Ok(sorted)
+        } else {
+            Err(Cycle(
+                Self::find_cycle(&successors, &in_degree)
+                    .into_iter()
+                    .map(|idx| unique[idx].clone())
+                    .collect(),
+            ))
+        }
+    }
+
+    /// A cycle among the nodes Kahn's algorithm left over, those with predecessors left.
+    ///
+    /// Every one of them has a predecessor that is left over too, so walking back from any of
+    /// them has to run into a node it has already seen.
+    fn find_cycle(successors: &[Vec<usize>], in_degree: &[usize]) -> Vec<usize> {
+        let mut predecessor = vec![None; successors.len()];
+        for (idx, next) in successors.iter().enumerate() {
+            if in_degree[idx] > 0 {
+                for &next in next {
+                    predecessor[next] = Some(idx);
+                }
+            }
+        }
+
+        let Some(mut idx) = in_degree.iter().position(|&degree| degree > 0) else {
+            return Vec::new();
+        };
+        let mut seen = HashMap::new();
+        let mut path = Vec::new();
+        loop {
+            if let Some(&start) = seen.get(&idx) {
+                let mut cycle = path.split_off(start);
+                cycle.reverse();
+                return cycle;
+            }
+            seen.insert(idx, path.len());
+            path.push(idx);
+            match predecessor[idx] {
+                Some(prev) => idx = prev,
+                None => return Vec::new(),
+            }
+        }
+    }
+}
+
+impl<N: Clone + Eq + Hash> FromIterator<(N, N)> for Graph<N> {
+    /// Builds a graph from its edges.
+    fn from_iter<I: IntoIterator<Item = (N, N)>>(edges: I) -> Self {
+        let mut graph = Self::new();
+        for (from, to) in edges {
+            graph.add_edge(from, to);
+        }
+        graph
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// An error which can be returned when sorting a [`Graph`] whose edges form a cycle.
+///
+/// Holds the nodes of one such cycle in the order of its edges, each leading to the next and the
+/// last one back to the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<N>(pub Vec<N>);
+
+impl<N: Debug + Display> Error for Cycle<N> {}
+
+impl<N: Display> Display for Cycle<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cycle ")?;
+        for node in &self.0 {
+            write!(f, "{node} -> ")?;
+        }
+        match self.0.first() {
+            Some(first) => write!(f, "{first}"),
+            None => write!(f, "()"),
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{Cycle, Graph};
+
+    #[test]
+    fn builds_graph() {
+        let mut graph = Graph::from_iter([(1, 2), (1, 3)]);
+        graph.add_node(4);
+        assert!(graph.contains_edge(&1, &2));
+        assert!(!graph.contains_edge(&2, &1));
+        assert!(graph.contains_node(&3) && graph.contains_node(&4));
+        assert!(!graph.contains_node(&5));
+        let mut successors = graph.successors(&1).copied().collect::<Vec<_>>();
+        successors.sort_unstable();
+        assert_eq!(successors, [2, 3]);
+        assert_eq!(graph.successors(&5).count(), 0);
+        assert_eq!(graph.nodes().count(), 4);
+    }
+
+    #[test]
+    fn sorts_topologically() {
+        let graph = Graph::from_iter([('a', 'b'), ('b', 'c'), ('a', 'd'), ('d', 'c')]);
+        let sorted = graph.toposort().unwrap();
+        let position = |node| sorted.iter().position(|&n| n == node).unwrap();
+        assert!(position('a') < position('b') && position('b') < position('c'));
+        assert!(position('a') < position('d') && position('d') < position('c'));
+
+        // ordered nodes stay where they are, unknown ones have no constraints
+        assert_eq!(
+            graph.toposort_subset(&['a', 'x', 'c']),
+            Ok(vec!['a', 'x', 'c'])
+        );
+        assert_eq!(
+            graph.toposort_subset(&['c', 'x', 'b', 'a', 'b']),
+            Ok(vec!['x', 'a', 'b', 'c'])
+        );
+        assert_eq!(graph.toposort_subset(&[]), Ok(vec![]));
+    }
+
+    #[test]
+    fn names_cycle() {
+        let graph = Graph::from_iter([(1, 2), (2, 3), (3, 4), (4, 2), (4, 5)]);
+        let Err(Cycle(cycle)) = graph.toposort() else {
+            panic!("2 -> 3 -> 4 -> 2 is a cycle");
+        };
+        assert_eq!(cycle.len(), 3);
+        for (from, to) in cycle.iter().zip(cycle.iter().cycle().skip(1)) {
+            assert!(graph.contains_edge(from, to));
+        }
+
+        // the cycle only matters if all of its nodes are sorted
+        assert_eq!(graph.toposort_subset(&[4, 3, 1]), Ok(vec![3, 4, 1]));
+        assert_eq!(
+            graph.toposort_subset(&[4, 3, 2]).unwrap_err().to_string(),
+            "cycle 2 -> 3 -> 4 -> 2"
+        );
+        assert_eq!(
+            Graph::from_iter([(7, 7)]).toposort().unwrap_err(),
+            Cycle(vec![7])
+        );
+    }
+}
@@ -0,0 +1,472 @@
+use crate::geometry::Point;
+use std::error::Error;
+use std::fmt::Display;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+/// Offsets of the 4 orthogonal neighbours: up, down, left, right.
+const NEIGHBOURS_4: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+/// Offsets of the 8 surrounding neighbours, in reading order.
+const NEIGHBOURS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rectangular grid of cells stored in row-major order, indexed by `(row, column)`.
+///
+/// # Display
+/// A grid displays one row per line, with the cells of a row concatenated.
+///
+/// ```
+/// # use advent_of_code::grid::Grid;
+/// let grid: Grid<char> = "ab\ncd".parse().unwrap();
+/// assert_eq!(grid[(1, 0)], 'c');
+/// assert_eq!(grid.to_string(), "ab\ncd");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid of the given size with every cell set to `value`.
+    pub fn new(rows: usize, cols: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: vec![value; rows * cols],
+            rows,
+            cols,
+        }
+    }
+
+    /// Creates a grid from a list of rows, returns an error if the rows differ in length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, GridError> {
+        let cols = rows.first().map_or(0, Vec::len);
+        let n_rows = rows.len();
+        let mut cells = Vec::with_capacity(n_rows * cols);
+        for (row, cells_in_row) in rows.into_iter().enumerate() {
+            if cells_in_row.len() != cols {
+                return Err(GridError::Ragged {
+                    row,
+                    expected: cols,
+                    found: cells_in_row.len(),
+                });
+            }
+            cells.extend(cells_in_row);
+        }
+
+        Ok(Self {
+            cells,
+            rows: n_rows,
+            cols,
+        })
+    }
+
+    /// Parses a grid with one row per line, converting every character with `f`.
+    /// Returns an error naming the position of the first character `f` rejects.
+    pub fn parse_with(
+        input: &str,
+        mut f: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, GridError> {
+        let mut rows = Vec::new();
+        for (row, line) in input.lines().enumerate() {
+            let cells = line
+                .chars()
+                .enumerate()
+                .map(|(col, c)| f(c).ok_or(GridError::InvalidCell { row, col, cell: c }))
+                .collect::<Result<Vec<_>, _>>()?;
+            rows.push(cells);
+        }
+
+        Self::from_rows(rows)
+    }
+
+    /// The number of rows.
+    pub const fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The number of columns.
+    pub const fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns `true` if `(row, column)` lies inside the grid.
+    pub const fn contains(&self, (row, col): (usize, usize)) -> bool {
+        row < self.rows && col < self.cols
+    }
+
+    /// Returns a reference to the cell at `(row, column)`, or [`None`] if it is out of bounds.
+    pub fn get(&self, pos: (usize, usize)) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.0 * self.cols + pos.1])
+    }
+
+    /// Returns a mutable reference to the cell at `(row, column)`, or [`None`] if it is out of bounds.
+    pub fn get_mut(&mut self, pos: (usize, usize)) -> Option<&mut T> {
+        if self.contains(pos) {
+            Some(&mut self.cells[pos.0 * self.cols + pos.1])
+        } else {
+            None
+        }
+    }
+
+    /// Returns a reference to the cell at `point`, or [`None`] if it is out of bounds.
+    pub fn at(&self, point: Point) -> Option<&T> {
+        self.get(point.to_index()?)
+    }
+
+    /// Returns a mutable reference to the cell at `point`, or [`None`] if it is out of bounds.
+    pub fn at_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.get_mut(point.to_index()?)
+    }
+
+    /// Converts a signed `(row, column)` into a position, or [`None`] if it is out of bounds.
+    pub fn checked_pos(&self, (row, col): (isize, isize)) -> Option<(usize, usize)> {
+        let pos = (usize::try_from(row).ok()?, usize::try_from(col).ok()?);
+        self.contains(pos).then_some(pos)
+    }
+
+    /// Returns the position `offset` away from `pos`, or [`None`] if it is out of bounds.
+    pub fn offset(
+        &self,
+        (row, col): (usize, usize),
+        (dr, dc): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let pos = (row.checked_add_signed(dr)?, col.checked_add_signed(dc)?);
+        self.contains(pos).then_some(pos)
+    }
+
+    /// Iterates over the up to 4 orthogonal neighbours of `pos` inside the grid.
+    pub fn neighbours4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        NEIGHBOURS_4
+            .iter()
+            .filter_map(move |&offset| self.offset(pos, offset))
+    }
+
+    /// Iterates over the up to 8 surrounding neighbours of `pos` inside the grid, diagonals included.
+    pub fn neighbours8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        NEIGHBOURS_8
+            .iter()
+            .filter_map(move |&offset| self.offset(pos, offset))
+    }
+
+    /// Iterates over all positions in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let cols = self.cols;
+        (0..self.rows).flat_map(move |row| (0..cols).map(move |col| (row, col)))
+    }
+
+    /// Iterates over all positions and cells in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// Returns the position of the first cell, in row-major order, that matches the predicate.
+    pub fn find(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        self.iter()
+            .find_map(|(pos, cell)| predicate(cell).then_some(pos))
+    }
+
+    /// Iterates over the positions of all cells equal to `value`.
+    pub fn positions_of<'a>(&'a self, value: &'a T) -> impl Iterator<Item = (usize, usize)> + 'a
+    where
+        T: PartialEq,
+    {
+        self.iter()
+            .filter_map(move |(pos, cell)| (cell == value).then_some(pos))
+    }
+
+    /// Returns the cells of a row.
+    ///
+    /// # Panics
+    /// Panics if the row is out of bounds.
+    pub fn row(&self, row: usize) -> &[T] {
+        assert!(row < self.rows, "row {row} out of bounds");
+        &self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /// Iterates over the cells of a column, from top to bottom.
+    ///
+    /// # Panics
+    /// Panics if the column is out of bounds.
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        assert!(col < self.cols, "column {col} out of bounds");
+        self.cells.iter().skip(col).step_by(self.cols)
+    }
+
+    /// Iterates over all rows, from top to bottom.
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` rejects a size of 0, which only happens for a grid without cells.
+        self.cells.chunks(self.cols.max(1))
+    }
+
+    /// Creates a grid of the same size by applying `f` to every cell.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+
+    /// Builds a grid of the given size by calling `f` for every position in row-major order.
+    fn from_fn(rows: usize, cols: usize, mut f: impl FnMut((usize, usize)) -> T) -> Self {
+        let cells = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (row, col)))
+            .map(&mut f)
+            .collect();
+        Self { cells, rows, cols }
+    }
+
+    /// Returns the grid mirrored along its main diagonal, rows become columns.
+    #[must_use]
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_fn(self.cols, self.rows, |(row, col)| self[(col, row)].clone())
+    }
+
+    /// Returns the grid rotated by 90 degrees clockwise.
+    #[must_use]
+    pub fn rotate_cw(&self) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_fn(self.cols, self.rows, |(row, col)| {
+            self[(self.rows - 1 - col, row)].clone()
+        })
+    }
+
+    /// Returns the grid rotated by 90 degrees counter-clockwise.
+    #[must_use]
+    pub fn rotate_ccw(&self) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_fn(self.cols, self.rows, |(row, col)| {
+            self[(col, self.cols - 1 - row)].clone()
+        })
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &Self::Output {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("position {pos:?} out of bounds"))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, pos: (usize, usize)) -> &mut Self::Output {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("position {pos:?} out of bounds"))
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &Self::Output {
+        self.at(point)
+            .unwrap_or_else(|| panic!("point {point} out of bounds"))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut Self::Output {
+        self.at_mut(point)
+            .unwrap_or_else(|| panic!("point {point} out of bounds"))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, row) in self.iter_rows().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Grid<char> {
+    type Err = GridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, Some)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// An error which can be returned when building a [`Grid`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum GridError {
+    /// A row does not have the same length as the first row.
+    Ragged {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A character could not be converted into a cell.
+    InvalidCell { row: usize, col: usize, cell: char },
+}
+
+impl Error for GridError {}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ragged {
+                row,
+                expected,
+                found,
+            } => write!(f, "row {} has {found} cells, expecting {expected}", row + 1),
+            Self::InvalidCell { row, col, cell } => write!(
+                f,
+                "invalid cell {cell:?} at line {}, column {}",
+                row + 1,
+                col + 1
+            ),
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{Grid, GridError};
+    use crate::geometry::Point;
+
+    fn grid() -> Grid<char> {
+        "abc\ndef".parse().unwrap()
+    }
+
+    #[test]
+    fn parses_and_displays() {
+        let grid = grid();
+        assert_eq!((grid.rows(), grid.cols()), (2, 3));
+        assert_eq!(grid[(0, 2)], 'c');
+        assert_eq!(grid[(1, 0)], 'd');
+        assert_eq!(grid.to_string(), "abc\ndef");
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "abc\nde".parse::<Grid<char>>(),
+            Err(GridError::Ragged {
+                row: 1,
+                expected: 3,
+                found: 2
+            })
+        );
+        let err = Grid::parse_with("12\n3x", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!(
+            err,
+            GridError::InvalidCell {
+                row: 1,
+                col: 1,
+                cell: 'x'
+            }
+        );
+        assert_eq!(err.to_string(), "invalid cell 'x' at line 2, column 2");
+    }
+
+    #[test]
+    fn checked_access() {
+        let grid = grid();
+        assert_eq!(grid.get((1, 2)), Some(&'f'));
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.get((0, 3)), None);
+        assert_eq!(grid.checked_pos((-1, 0)), None);
+        assert_eq!(grid.checked_pos((1, 1)), Some((1, 1)));
+        assert_eq!(grid.offset((0, 0), (0, -1)), None);
+        assert_eq!(grid.offset((0, 0), (1, 2)), Some((1, 2)));
+    }
+
+    #[test]
+    fn point_access() {
+        let mut grid = grid();
+        assert_eq!(grid.at(Point::new(1, 2)), Some(&'f'));
+        assert_eq!(grid.at(Point::new(-1, 0)), None);
+        assert_eq!(grid.at(Point::new(0, 3)), None);
+        grid[Point::new(0, 0)] = 'z';
+        assert_eq!(grid[(0, 0)], 'z');
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = grid();
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1)]
+        );
+        assert_eq!(
+            grid.neighbours4((1, 1)).collect::<Vec<_>>(),
+            [(0, 1), (1, 0), (1, 2)]
+        );
+        assert_eq!(
+            grid.neighbours8((0, 1)).collect::<Vec<_>>(),
+            [(0, 0), (0, 2), (1, 0), (1, 1), (1, 2)]
+        );
+    }
+
+    #[test]
+    fn searches() {
+        let grid: Grid<char> = "a.b\n.a.".parse().unwrap();
+        assert_eq!(grid.find(|&c| c == 'b'), Some((0, 2)));
+        assert_eq!(grid.find(|&c| c == 'z'), None);
+        assert_eq!(
+            grid.positions_of(&'a').collect::<Vec<_>>(),
+            [(0, 0), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn views() {
+        let grid = grid();
+        assert_eq!(grid.row(1), ['d', 'e', 'f']);
+        assert_eq!(grid.column(1).copied().collect::<String>(), "be");
+        assert_eq!(grid.iter_rows().count(), 2);
+        assert_eq!(grid.map(|c| c.to_ascii_uppercase()).to_string(), "ABC\nDEF");
+    }
+
+    #[test]
+    fn transforms() {
+        let grid = grid();
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf");
+        assert_eq!(grid.rotate_cw().to_string(), "da\neb\nfc");
+        assert_eq!(grid.rotate_ccw().to_string(), "cf\nbe\nad");
+        assert_eq!(grid.rotate_cw().rotate_ccw(), grid);
+        assert_eq!(grid.rotate_cw().rotate_cw().rotate_cw().rotate_cw(), grid);
+    }
+
+    #[test]
+    fn empty_grid() {
+        let grid: Grid<char> = "".parse().unwrap();
+        assert_eq!((grid.rows(), grid.cols()), (0, 0));
+        assert_eq!(grid.positions().count(), 0);
+        assert_eq!(grid.to_string(), "");
+    }
+}
@@ -1,3 +1,15 @@
+pub mod components;
+pub mod geometry;
+pub mod graph;
+pub mod grid;
+pub mod linalg;
+pub mod math;
+pub mod memo;
+pub mod parse;
+pub mod rational;
+pub mod render;
+pub mod scan;
+pub mod search;
 pub mod template;
 
 // Use this file to add helper functions and additional modules.
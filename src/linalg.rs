@@ -0,0 +1,314 @@
+use crate::math::{crt, gcd, lcm, mod_inverse};
+use crate::rational::Rational;
+
+/// The solutions of a linear system `A x = b`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Solution {
+    /// The equations contradict each other.
+    None,
+    /// Exactly one solution.
+    Unique(Vec<Rational>),
+    /// Infinitely many solutions, because the system is underdetermined.
+    Parametric(Family),
+}
+
+/// The solutions `particular + t_1 * directions[0] + t_2 * directions[1] + ...` for any `t_i`.
+///
+/// Each direction belongs to one free variable: it is 1 at that variable, 0 at the other free
+/// variables, and the particular solution is 0 at all free variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Family {
+    pub particular: Vec<Rational>,
+    /// Indices of the free variables, in the order of `directions`.
+    pub free: Vec<usize>,
+    pub directions: Vec<Vec<Rational>>,
+}
+
+/// Solves `coefficients * x = rhs` exactly by Gauss-Jordan elimination.
+///
+/// `coefficients` has one row per equation and one column per variable.
+///
+/// # Panics
+/// Panics if the rows of `coefficients` differ in length, or there is not one `rhs` per row.
+pub fn solve(coefficients: &[Vec<Rational>], rhs: &[Rational]) -> Solution {
+    assert_eq!(
+        coefficients.len(),
+        rhs.len(),
+        "expecting one rhs per equation"
+    );
+    let vars = coefficients.first().map_or(0, Vec::len);
+    let mut rows = coefficients
+        .iter()
+        .zip(rhs)
+        .map(|(row, &b)| {
+            assert_eq!(row.len(), vars, "ragged coefficient matrix");
+            let mut row = row.clone();
+            row.push(b);
+            row
+        })
+        .collect::<Vec<_>>();
+
+    // reduce to row echelon form with 1 at every pivot and 0 above and below it.
+    let mut pivots = Vec::new();
+    for col in 0..vars {
+        let rank = pivots.len();
+        let Some(pivot_row) = (rank..rows.len()).find(|&row| !rows[row][col].is_zero()) else {
+            continue;
+        };
+        rows.swap(rank, pivot_row);
+        let scale = rows[rank][col].recip().expect("pivot is not 0");
+        for value in &mut rows[rank] {
+            *value *= scale;
+        }
+        let pivot = rows[rank].clone();
+        for (idx, row) in rows.iter_mut().enumerate() {
+            let factor = row[col];
+            if idx == rank || factor.is_zero() {
+                continue;
+            }
+            for (value, &p) in row[col..].iter_mut().zip(&pivot[col..]) {
+                *value -= factor * p;
+            }
+        }
+        pivots.push(col);
+    }
+
+    // the remaining rows read 0 = b.
+    if rows[pivots.len()..].iter().any(|row| !row[vars].is_zero()) {
+        return Solution::None;
+    }
+
+    let mut particular = vec![Rational::ZERO; vars];
+    for (row, &col) in pivots.iter().enumerate() {
+        particular[col] = rows[row][vars];
+    }
+    if pivots.len() == vars {
+        return Solution::Unique(particular);
+    }
+
+    let free = (0..vars)
+        .filter(|col| !pivots.contains(col))
+        .collect::<Vec<_>>();
+    let directions = free
+        .iter()
+        .map(|&f| {
+            let mut direction = vec![Rational::ZERO; vars];
+            direction[f] = Rational::ONE;
+            for (row, &col) in pivots.iter().enumerate() {
+                direction[col] = -rows[row][f];
+            }
+            direction
+        })
+        .collect();
+
+    Solution::Parametric(Family {
+        particular,
+        free,
+        directions,
+    })
+}
+
+impl Solution {
+    /// The non-negative integer solution with the lowest total `costs[i] * x[i]`, together with
+    /// that cost. Ties are broken towards the smallest value of the free variable.
+    ///
+    /// Returns [`None`] if there is no such solution, if the cost is unbounded below, or if the
+    /// family has more than one free variable, which is not supported.
+    ///
+    /// # Panics
+    /// Panics if there is not one cost per variable.
+    pub fn min_cost_non_negative(&self, costs: &[i128]) -> Option<(Vec<i128>, i128)> {
+        let values = match self {
+            Self::None => return None,
+            Self::Unique(values) => values
+                .iter()
+                .map(|value| value.to_integer().filter(|&n| n >= 0))
+                .collect::<Option<Vec<_>>>()?,
+            Self::Parametric(family) => family.min_cost_non_negative(costs)?,
+        };
+        assert_eq!(values.len(), costs.len(), "expecting one cost per variable");
+        let cost = values.iter().zip(costs).map(|(x, c)| x * c).sum();
+        Some((values, cost))
+    }
+}
+
+impl Family {
+    /// See [`Solution::min_cost_non_negative`].
+    fn min_cost_non_negative(&self, costs: &[i128]) -> Option<Vec<i128>> {
+        let [direction] = self.directions.as_slice() else {
+            return None;
+        };
+        assert_eq!(
+            direction.len(),
+            costs.len(),
+            "expecting one cost per variable"
+        );
+
+        // the free variable is t itself, so t has to be an integer that also makes every other
+        // variable integral: t ≡ residue (mod modulus) for each of them.
+        let mut congruences = Vec::new();
+        for (&p, &d) in self.particular.iter().zip(direction) {
+            let scale = lcm(p.denom(), d.denom())?;
+            let (p, d) = (
+                p.numer() * (scale / p.denom()),
+                d.numer() * (scale / d.denom()),
+            );
+            // p + t * d ≡ 0 (mod scale)
+            let g = gcd(d, scale);
+            if p % g != 0 {
+                return None;
+            }
+            let modulus = scale / g;
+            let inverse = mod_inverse(d / g, modulus)?;
+            congruences.push((-p / g * inverse, modulus));
+        }
+        let (t0, step) = crt(congruences)?;
+
+        // every variable has to stay non-negative, which bounds t from one side each.
+        let (mut lower, mut upper) = (None::<i128>, None::<i128>);
+        for (&p, &d) in self.particular.iter().zip(direction) {
+            match d.signum() {
+                0 if p < Rational::ZERO => return None,
+                0 => {}
+                1 => {
+                    let bound = (-p / d).ceil();
+                    lower = Some(lower.map_or(bound, |lower| lower.max(bound)));
+                }
+                _ => {
+                    let bound = (-p / d).floor();
+                    upper = Some(upper.map_or(bound, |upper| upper.min(bound)));
+                }
+            }
+        }
+
+        // t = t0 + k * step, the cost changes linearly in t.
+        let k_min = lower.map(|lower| Rational::new(lower - t0, step).ceil());
+        let k_max = upper.map(|upper| Rational::new(upper - t0, step).floor());
+        if let (Some(k_min), Some(k_max)) = (k_min, k_max) {
+            if k_min > k_max {
+                return None;
+            }
+        }
+        let slope = direction
+            .iter()
+            .zip(costs)
+            .fold(Rational::ZERO, |acc, (&d, &c)| acc + d * c.into());
+        let k = match slope.signum() {
+            1 => k_min?,
+            -1 => k_max?,
+            _ => k_min.or(k_max).unwrap_or(0),
+        };
+
+        let t = Rational::from(t0 + k * step);
+        self.particular
+            .iter()
+            .zip(direction)
+            .map(|(&p, &d)| (p + t * d).to_integer())
+            .collect()
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{solve, Family, Solution};
+    use crate::rational::Rational;
+
+    fn matrix(rows: &[&[i128]]) -> Vec<Vec<Rational>> {
+        rows.iter()
+            .map(|row| row.iter().map(|&n| n.into()).collect())
+            .collect()
+    }
+
+    fn vector(values: &[i128]) -> Vec<Rational> {
+        values.iter().map(|&n| n.into()).collect()
+    }
+
+    #[test]
+    fn unique_solution() {
+        let a = matrix(&[&[94, 22], &[34, 67]]);
+        let solution = solve(&a, &vector(&[8400, 5400]));
+        assert_eq!(solution, Solution::Unique(vector(&[80, 40])));
+        assert_eq!(
+            solution.min_cost_non_negative(&[3, 1]),
+            Some((vec![80, 40], 280))
+        );
+
+        // a fractional solution has no integer solution
+        let solution = solve(&a, &vector(&[8401, 5400]));
+        assert!(matches!(solution, Solution::Unique(_)));
+        assert_eq!(solution.min_cost_non_negative(&[3, 1]), None);
+    }
+
+    #[test]
+    fn inconsistent_system() {
+        let a = matrix(&[&[1, 2], &[2, 4]]);
+        assert_eq!(solve(&a, &vector(&[3, 7])), Solution::None);
+    }
+
+    #[test]
+    fn parametric_solution() {
+        // x + 2y = 4, written twice
+        let a = matrix(&[&[1, 2], &[2, 4]]);
+        let solution = solve(&a, &vector(&[4, 8]));
+        assert_eq!(
+            solution,
+            Solution::Parametric(Family {
+                particular: vector(&[4, 0]),
+                free: vec![1],
+                directions: vec![vec![Rational::from(-2_i128), Rational::ONE]],
+            })
+        );
+        // (4, 0), (2, 1) and (0, 2) are the non-negative integer solutions
+        assert_eq!(
+            solution.min_cost_non_negative(&[3, 1]),
+            Some((vec![0, 2], 2))
+        );
+        assert_eq!(
+            solution.min_cost_non_negative(&[1, 3]),
+            Some((vec![4, 0], 4))
+        );
+        // all of them cost the same, the free variable y is kept as small as possible
+        assert_eq!(
+            solution.min_cost_non_negative(&[2, 4]),
+            Some((vec![4, 0], 8))
+        );
+    }
+
+    #[test]
+    fn parametric_integrality() {
+        // x = 7/2 - 3/2 y, so y has to be odd and (2, 1) is the only non-negative solution
+        let a = matrix(&[&[4, 6]]);
+        let solution = solve(&a, &vector(&[14]));
+        assert_eq!(
+            solution.min_cost_non_negative(&[1, 1]),
+            Some((vec![2, 1], 3))
+        );
+        // 4x + 6y = 13 has no integer solution at all
+        let solution = solve(&a, &vector(&[13]));
+        assert_eq!(solution.min_cost_non_negative(&[1, 1]), None);
+        // 4x - 6y = 2 has non-negative solutions of any size, the cheapest one is (2, 1)
+        let solution = solve(&matrix(&[&[4, -6]]), &vector(&[2]));
+        assert_eq!(
+            solution.min_cost_non_negative(&[1, 1]),
+            Some((vec![2, 1], 3))
+        );
+        // ...and the cost is unbounded below if y earns tokens
+        assert_eq!(solution.min_cost_non_negative(&[1, -2]), None);
+    }
+
+    #[test]
+    fn more_unknowns_than_equations() {
+        let a = matrix(&[&[1, 1, 1]]);
+        let Solution::Parametric(family) = solve(&a, &vector(&[3])) else {
+            panic!("expecting a parametric solution");
+        };
+        assert_eq!(family.free, [1, 2]);
+        assert_eq!(family.directions.len(), 2);
+        assert_eq!(
+            Solution::Parametric(family).min_cost_non_negative(&[1, 1, 1]),
+            None
+        );
+    }
+}
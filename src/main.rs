@@ -7,7 +7,7 @@ use advent_of_code::template::Day;
 use std::process;
 
 mod args {
-    use advent_of_code::template::Day;
+    use advent_of_code::template::{parse_part, Day, Input};
     use std::process;
 
     pub enum AppArguments {
@@ -26,7 +26,12 @@ mod args {
             day: Day,
             release: bool,
             dhat: bool,
+            profile: bool,
             submit: Option<u8>,
+            input: Input,
+            part: Option<u8>,
+            /// Unknown arguments, forwarded to the solution (e.g. `--display`).
+            extra: Vec<String>,
         },
         All {
             release: bool,
@@ -43,7 +48,7 @@ mod args {
     pub fn parse() -> Result<AppArguments, Box<dyn std::error::Error>> {
         let mut args = pico_args::Arguments::from_env();
 
-        let app_args = match args.subcommand()?.as_deref() {
+        let mut app_args = match args.subcommand()?.as_deref() {
             Some("all") => AppArguments::All {
                 release: args.contains("--release"),
             },
@@ -71,8 +76,12 @@ mod args {
             Some("solve") => AppArguments::Solve {
                 day: args.free_from_str()?,
                 release: args.contains("--release"),
-                submit: args.opt_value_from_str("--submit")?,
+                submit: args.opt_value_from_fn("--submit", parse_part)?,
                 dhat: args.contains("--dhat"),
+                profile: args.contains("--profile"),
+                input: Input::parse(&mut args)?,
+                part: args.opt_value_from_fn("--part", parse_part)?,
+                extra: vec![],
             },
             #[cfg(feature = "today")]
             Some("today") => AppArguments::Today,
@@ -87,7 +96,13 @@ mod args {
         };
 
         let remaining = args.finish();
-        if !remaining.is_empty() {
+        if let AppArguments::Solve { extra, .. } = &mut app_args {
+            extra.extend(
+                remaining
+                    .iter()
+                    .map(|arg| arg.to_string_lossy().into_owned()),
+            );
+        } else if !remaining.is_empty() {
             eprintln!("Warning: unknown argument(s): {remaining:?}.");
         }
 
@@ -120,8 +135,12 @@ fn main() {
                 day,
                 release,
                 dhat,
+                profile,
                 submit,
-            } => solve::handle(day, release, dhat, submit),
+                input,
+                part,
+                extra,
+            } => solve::handle(day, release, dhat, profile, submit, &input, part, &extra),
             #[cfg(feature = "today")]
             AppArguments::Today => {
                 match Day::today() {
@@ -0,0 +1,248 @@
+//! Number theory over `i128`.
+//!
+//! Operations whose result may not fit into an `i128` return [`None`] instead of overflowing,
+//! modular products never overflow. Moduli have to be positive.
+
+/// The greatest common divisor of `a` and `b`, always non-negative. `gcd(0, 0)` is 0.
+///
+/// # Panics
+/// Panics if the result is 2^127, i.e. for `gcd(i128::MIN, 0)` and `gcd(i128::MIN, i128::MIN)`.
+pub fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    i128::try_from(a).expect("gcd overflows i128")
+}
+
+/// The least common multiple of `a` and `b`, always non-negative. Returns [`None`] on overflow.
+pub fn lcm(a: i128, b: i128) -> Option<i128> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)?.checked_abs()
+}
+
+/// Extended Euclidean algorithm, returns `(g, x, y)` with `a * x + b * y == g` and `g == gcd(a, b)`.
+///
+/// The coefficients are bounded by `|b / g|` and `|a / g|`, so only an operand of `i128::MIN`
+/// can overflow, in which case this returns [`None`].
+pub fn extended_gcd(a: i128, b: i128) -> Option<(i128, i128, i128)> {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1i128, 0i128);
+    let (mut old_y, mut y) = (0i128, 1i128);
+    while r != 0 {
+        let q = old_r.checked_div(r)?;
+        (old_r, r) = (r, old_r.checked_sub(q.checked_mul(r)?)?);
+        (old_x, x) = (x, old_x.checked_sub(q.checked_mul(x)?)?);
+        (old_y, y) = (y, old_y.checked_sub(q.checked_mul(y)?)?);
+    }
+
+    if old_r < 0 {
+        Some((
+            old_r.checked_neg()?,
+            old_x.checked_neg()?,
+            old_y.checked_neg()?,
+        ))
+    } else {
+        Some((old_r, old_x, old_y))
+    }
+}
+
+/// The inverse of `a` modulo `m` in `0..m`, or [`None`] if `a` and `m` are not coprime.
+pub fn mod_inverse(a: i128, m: i128) -> Option<i128> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m)?;
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// `a * b` modulo `m` in `0..m`, without overflowing for any modulus.
+pub fn mul_mod(a: i128, b: i128, m: i128) -> i128 {
+    let (a, b) = (a.rem_euclid(m), b.rem_euclid(m));
+    if let Some(product) = a.checked_mul(b) {
+        return product % m;
+    }
+
+    // double-and-add, both operands are below m <= 2^127 - 1 so their sum fits into a u128.
+    let (mut a, mut b, m) = (a.unsigned_abs(), b.unsigned_abs(), m.unsigned_abs());
+    let mut product = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            product = (product + a) % m;
+        }
+        a = (a + a) % m;
+        b >>= 1;
+    }
+    product.try_into().expect("product is below the modulus")
+}
+
+/// `base` to the power of `exp` modulo `m` in `0..m`, by repeated squaring.
+pub fn pow_mod(base: i128, mut exp: u128, m: i128) -> i128 {
+    let mut base = base.rem_euclid(m);
+    let mut result = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Solves the system `x ≡ residue (mod modulus)` for each `(residue, modulus)` pair.
+///
+/// The moduli do not have to be pairwise coprime. Returns `(x, l)` where `l` is the lcm of the moduli
+/// and `x` in `0..l` is the only solution modulo `l`, or [`None`] if the congruences contradict each
+/// other or `l` overflows. An empty system is solved by `(0, 1)`.
+///
+/// ```
+/// # use advent_of_code::math::crt;
+/// // x ≡ 2 (mod 6) and x ≡ 4 (mod 8) share the factor 2
+/// assert_eq!(crt([(2, 6), (4, 8)]), Some((20, 24)));
+/// assert_eq!(crt([(1, 6), (4, 8)]), None);
+/// ```
+pub fn crt(congruences: impl IntoIterator<Item = (i128, i128)>) -> Option<(i128, i128)> {
+    let (mut x, mut l) = (0, 1);
+    for (residue, modulus) in congruences {
+        let residue = residue.rem_euclid(modulus);
+        let g = gcd(l, modulus);
+        let diff = residue - x;
+        if diff % g != 0 {
+            return None;
+        }
+
+        // x + l * t ≡ residue (mod modulus)  <=>  (l / g) * t ≡ diff / g (mod modulus / g)
+        let reduced = modulus / g;
+        let inverse = mod_inverse(l / g, reduced)?;
+        let t = mul_mod(diff / g, inverse, reduced);
+        let next_l = (l / g).checked_mul(modulus)?;
+        // l * t < next_l, and x < l, so only the final addition may overflow.
+        x = (x.checked_add(l * t)?) % next_l;
+        l = next_l;
+    }
+
+    Some((x, l))
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{crt, extended_gcd, gcd, lcm, mod_inverse, mul_mod, pow_mod};
+    use quickcheck::{quickcheck, TestResult};
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(-12, 18), 6);
+        assert_eq!(gcd(0, -7), 7);
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(lcm(4, -6), Some(12));
+        assert_eq!(lcm(0, 6), Some(0));
+        assert_eq!(lcm(i128::MAX, i128::MAX - 1), None);
+    }
+
+    #[test]
+    fn extended_gcd_bounds() {
+        assert_eq!(extended_gcd(240, 46), Some((2, -9, 47)));
+        assert_eq!(extended_gcd(-4, 0), Some((4, -1, 0)));
+        assert_eq!(extended_gcd(i128::MAX, i128::MAX - 1), Some((1, 1, -1)));
+        assert_eq!(extended_gcd(i128::MIN, 0), None);
+        assert_eq!(extended_gcd(0, i128::MIN), None);
+    }
+
+    #[test]
+    fn modular_arithmetic() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(4, 10), None);
+        assert_eq!(pow_mod(2, 10, 1000), 24);
+        assert_eq!(pow_mod(5, 0, 1), 0);
+
+        // 2^127 - 1 is prime, so Fermat's little theorem holds for it
+        let p = i128::MAX;
+        assert_eq!(pow_mod(3, p.unsigned_abs() - 1, p), 1);
+        assert_eq!(mul_mod(p - 1, p - 1, p), 1);
+    }
+
+    #[test]
+    fn chinese_remainder() {
+        assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt([(2, 6), (4, 8)]), Some((20, 24)));
+        assert_eq!(crt([(1, 6), (4, 8)]), None);
+        assert_eq!(
+            crt([(-1, 101), (-1, 103)]),
+            Some((101 * 103 - 1, 101 * 103))
+        );
+        assert_eq!(crt([]), Some((0, 1)));
+        assert_eq!(crt([(0, i128::MAX), (0, i128::MAX - 1)]), None);
+    }
+
+    quickcheck! {
+        fn bezout_identity(a: i64, b: i64) -> bool {
+            let (a, b) = (i128::from(a), i128::from(b));
+            let Some((g, x, y)) = extended_gcd(a, b) else {
+                return false;
+            };
+            g == gcd(a, b) && a * x + b * y == g
+        }
+
+        fn gcd_divides_and_lcm_is_multiple(a: i64, b: i64) -> TestResult {
+            if a == 0 || b == 0 {
+                return TestResult::discard();
+            }
+            let (a, b) = (i128::from(a), i128::from(b));
+            let (g, l) = (gcd(a, b), lcm(a, b).unwrap());
+            TestResult::from_bool(
+                a % g == 0 && b % g == 0 && l % a == 0 && l % b == 0 && g * l == (a * b).abs(),
+            )
+        }
+
+        fn inverse_multiplies_to_one(a: i64, m: u32) -> TestResult {
+            let (a, m) = (i128::from(a), i128::from(m));
+            if m < 2 {
+                return TestResult::discard();
+            }
+            TestResult::from_bool(match mod_inverse(a, m) {
+                Some(inv) => (0..m).contains(&inv) && mul_mod(a, inv, m) == 1,
+                None => gcd(a, m) != 1,
+            })
+        }
+
+        fn mul_mod_matches_wide_product(a: u64, b: u64, m: u64) -> TestResult {
+            if m == 0 {
+                return TestResult::discard();
+            }
+            let expected = u128::from(a) * u128::from(b) % u128::from(m);
+            TestResult::from_bool(
+                mul_mod(a.into(), b.into(), m.into()).unsigned_abs() == expected,
+            )
+        }
+
+        fn mul_mod_is_distributive_for_large_moduli(a: i128, b: i64, c: i64) -> bool {
+            let m = i128::MAX - 18;
+            let (b, c) = (i128::from(b), i128::from(c));
+            let sum = mul_mod(a, b, m).unsigned_abs() + mul_mod(a, c, m).unsigned_abs();
+            mul_mod(a, b + c, m).unsigned_abs() == sum % m.unsigned_abs()
+        }
+
+        fn pow_mod_matches_repeated_multiplication(base: i64, exp: u8, m: u32) -> TestResult {
+            if m == 0 {
+                return TestResult::discard();
+            }
+            let (base, m) = (i128::from(base), i128::from(m));
+            let expected = (0..exp).fold(1 % m, |acc, _| mul_mod(acc, base, m));
+            TestResult::from_bool(pow_mod(base, exp.into(), m) == expected)
+        }
+
+        fn crt_recovers_number(x: u32, moduli: Vec<u8>) -> TestResult {
+            let moduli = moduli.into_iter().filter(|&m| m > 0).take(8).collect::<Vec<_>>();
+            let x = i128::from(x);
+            let Some((solution, l)) = crt(moduli.iter().map(|&m| (x, i128::from(m)))) else {
+                return TestResult::failed();
+            };
+            let expected_l = moduli.iter().try_fold(1, |l, &m| lcm(l, m.into()));
+            TestResult::from_bool(Some(l) == expected_l && solution == x % l)
+        }
+    }
+}
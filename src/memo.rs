@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::Hash;
+
+/// A cache for memoizing recursive functions keyed on their arguments.
+///
+/// The function takes the memo as a parameter and wraps its body in [`Memo::get_or_compute`],
+/// which hands the memo back to the body so the recursive calls go through the same cache.
+///
+/// ```
+/// # use advent_of_code::memo::Memo;
+/// fn fib(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+///     if n < 2 {
+///         return n;
+///     }
+///     memo.get_or_compute(n, |memo| fib(memo, n - 1) + fib(memo, n - 2))
+/// }
+///
+/// assert_eq!(fib(&mut Memo::new(), 90), 2_880_067_194_370_816_120);
+/// ```
+///
+/// In debug builds the memo also counts cache hits and misses, see [`Memo::stats`].
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    #[cfg(debug_assertions)]
+    stats: Stats,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self {
+            cache: HashMap::new(),
+            #[cfg(debug_assertions)]
+            stats: Stats::default(),
+        }
+    }
+
+    /// Returns the cached value for `key`, or computes it with `f` and caches it.
+    ///
+    /// `f` gets the memo itself, so it can recurse into other keys. It must not recurse into
+    /// `key` again, which would never terminate.
+    pub fn get_or_compute(&mut self, key: K, f: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            #[cfg(debug_assertions)]
+            {
+                self.stats.hits += 1;
+            }
+            return value.clone();
+        }
+
+        #[cfg(debug_assertions)]
+        {
+            self.stats.misses += 1;
+        }
+        let value = f(self);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    /// Returns the cached value for `key` without computing it.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.cache.get(key)
+    }
+
+    /// The number of cached values.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Cache hits and misses so far, only tracked in debug builds.
+    #[cfg(debug_assertions)]
+    pub const fn stats(&self) -> Stats {
+        self.stats
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cache statistics of a [`Memo`].
+///
+/// # Display
+/// Stats display as `<hits> hits, <misses> misses (<rate>% hit rate)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lookups = self.hits + self.misses;
+        #[allow(clippy::cast_precision_loss)]
+        let rate = if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 * 100.0 / lookups as f64
+        };
+        write!(
+            f,
+            "{} hits, {} misses ({rate:.1}% hit rate)",
+            self.hits, self.misses
+        )
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::Memo;
+    #[cfg(debug_assertions)]
+    use super::Stats;
+
+    /// Counts the lattice paths from `(0, 0)` to `(row, col)` that only step down or right.
+    fn lattice_paths(memo: &mut Memo<(u64, u64), u64>, row: u64, col: u64) -> u64 {
+        if row == 0 || col == 0 {
+            return 1;
+        }
+        memo.get_or_compute((row, col), |memo| {
+            lattice_paths(memo, row - 1, col) + lattice_paths(memo, row, col - 1)
+        })
+    }
+
+    #[test]
+    fn memoizes_recursion() {
+        let mut memo = Memo::new();
+        // C(60, 30), far out of reach without the cache
+        assert_eq!(lattice_paths(&mut memo, 30, 30), 118_264_581_564_861_424);
+        assert_eq!(memo.len(), 900);
+        assert_eq!(memo.get(&(2, 2)), Some(&6));
+        assert_eq!(memo.get(&(31, 31)), None);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn counts_hits_and_misses() {
+        let mut memo = Memo::new();
+        lattice_paths(&mut memo, 3, 3);
+        // the 9 inner cells are computed once each, the other 4 of the 13 lookups are hits
+        assert_eq!(memo.stats(), Stats { hits: 4, misses: 9 });
+        lattice_paths(&mut memo, 3, 3);
+        assert_eq!(memo.stats().hits, 5);
+        assert_eq!(
+            Stats { hits: 1, misses: 3 }.to_string(),
+            "1 hits, 3 misses (25.0% hit rate)"
+        );
+    }
+}
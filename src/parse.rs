@@ -0,0 +1,251 @@
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, digit1, line_ending, multispace0, one_of, space0, space1},
+    combinator::{all_consuming, cut, eof, map_res, not, opt, recognize},
+    error::ErrorKind,
+    multi::separated_list1,
+    sequence::{pair, preceded, separated_pair, terminated, tuple},
+    Err, IResult,
+};
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// An unsigned integer, a run of ASCII digits.
+pub fn unsigned<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(digit1, str::parse)(input)
+}
+
+/// A signed integer, a run of ASCII digits with an optional leading `+` or `-`.
+pub fn signed<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(recognize(pair(opt(one_of("+-")), digit1)), str::parse)(input)
+}
+
+/// One or more items on a single line, separated by spaces or tabs.
+///
+/// ```
+/// # use advent_of_code::parse::{space_separated, unsigned};
+/// let (_, levels) = space_separated(unsigned::<u32>)("7 6  4\t2").unwrap();
+/// assert_eq!(levels, [7, 6, 4, 2]);
+/// ```
+pub fn space_separated<'a, O>(
+    item: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    separated_list1(space1, item)
+}
+
+/// One or more items separated by commas, e.g. `75,47,61`.
+pub fn comma_separated<'a, O>(
+    item: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    separated_list1(char(','), item)
+}
+
+/// One item per line.
+///
+/// Stops before an empty line or the end of the input. Once a line has started, `item` has to
+/// match it, so errors point into the offending line rather than at the end of the previous one.
+pub fn lines<'a, O>(
+    item: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    separated_list1(
+        terminated(line_ending, not(alt((line_ending, eof)))),
+        cut(item),
+    )
+}
+
+/// A line without any content, i.e. the separator between two blocks.
+pub fn blank_line(input: &str) -> IResult<&str, &str> {
+    recognize(tuple((line_ending, space0, line_ending)))(input)
+}
+
+/// One or more blocks separated by blank lines.
+///
+/// Like [`lines`], `block` has to match once another block has started.
+pub fn blocks<'a, O>(
+    block: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    separated_list1(
+        terminated(blank_line, not(terminated(multispace0, eof))),
+        cut(block),
+    )
+}
+
+/// A `key: values` line, such as `190: 10 19`.
+///
+/// ```
+/// # use advent_of_code::parse::{key_values, unsigned};
+/// let (_, (key, values)) = key_values(unsigned::<u64>, unsigned::<u64>)("190: 10 19").unwrap();
+/// assert_eq!((key, values), (190, vec![10, 19]));
+/// ```
+pub fn key_values<'a, K, V>(
+    key: impl FnMut(&'a str) -> IResult<&'a str, K>,
+    value: impl FnMut(&'a str) -> IResult<&'a str, V>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (K, Vec<V>)> {
+    separated_pair(key, pair(char(':'), space0), space_separated(value))
+}
+
+/// A labelled pair of signed integers, such as `p=0,4` or `v=-3,3`.
+///
+/// ```
+/// # use advent_of_code::parse::labelled_pair;
+/// let (rest, v) = labelled_pair::<i32>("v")("v=-3,3 ").unwrap();
+/// assert_eq!((rest, v), (" ", (-3, 3)));
+/// ```
+pub fn labelled_pair<'a, T: FromStr>(
+    label: &'a str,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (T, T)> {
+    preceded(
+        pair(tag(label), char('=')),
+        separated_pair(signed, char(','), signed),
+    )
+}
+
+/// Runs `parser` over the whole input, allowing trailing whitespace.
+///
+/// # Errors
+/// Returns a [`ParseError`] with the position where parsing failed, or where unexpected input
+/// starts if `parser` did not consume everything.
+pub fn parse_all<'a, O>(
+    input: &'a str,
+    parser: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> Result<O, ParseError> {
+    match all_consuming(terminated(parser, multispace0))(input) {
+        Ok((_, output)) => Ok(output),
+        Err(Err::Error(e) | Err::Failure(e)) => Err(ParseError::new(input, e.input, e.code)),
+        Err(Err::Incomplete(_)) => Err(ParseError::new(input, "", ErrorKind::Complete)),
+    }
+}
+
+/// An error which can be returned by [`parse_all`], positioned by line and column (both 1-based).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    /// The parser that failed.
+    pub kind: ErrorKind,
+    /// The rest of the offending line.
+    pub found: String,
+}
+
+impl ParseError {
+    /// Creates an error for `rest`, the remaining suffix of `input` where parsing failed.
+    fn new(input: &str, rest: &str, kind: ErrorKind) -> Self {
+        let offset = input.len() - rest.len();
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            kind,
+            found: rest.lines().next().unwrap_or_default().to_string(),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "parse error at line {}, column {}: {} failed",
+            self.line,
+            self.column,
+            self.kind.description()
+        )?;
+        if self.found.is_empty() {
+            write!(f, " at the end of the line")
+        } else {
+            write!(f, " on {:?}", self.found)
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        blank_line, blocks, comma_separated, key_values, labelled_pair, lines, parse_all, signed,
+        space_separated, unsigned, ParseError,
+    };
+    use nom::{character::complete::char, error::ErrorKind, sequence::separated_pair};
+
+    #[test]
+    fn parses_integers() {
+        assert_eq!(unsigned::<u32>("42,"), Ok((",", 42)));
+        assert!(unsigned::<u32>("-42").is_err());
+        assert_eq!(signed::<i64>("-42"), Ok(("", -42)));
+        assert_eq!(signed::<i64>("+42"), Ok(("", 42)));
+        assert!(signed::<i64>("-").is_err());
+        // overflow is an error rather than a wrapped or default value
+        assert!(unsigned::<u8>("256").is_err());
+    }
+
+    #[test]
+    fn parses_lists() {
+        assert_eq!(
+            space_separated(signed::<i32>)("3   -4 5\n"),
+            Ok(("\n", vec![3, -4, 5]))
+        );
+        assert_eq!(
+            comma_separated(unsigned::<u32>)("75,47,61 "),
+            Ok((" ", vec![75, 47, 61]))
+        );
+        assert_eq!(
+            key_values(unsigned::<u64>, unsigned::<u64>)("3267: 81 40 27"),
+            Ok(("", (3267, vec![81, 40, 27])))
+        );
+        assert_eq!(
+            labelled_pair::<i32>("p")("p=0,4 v=3,-3"),
+            Ok((" v=3,-3", (0, 4)))
+        );
+    }
+
+    #[test]
+    fn parses_lines_and_blocks() {
+        let rules = || lines(separated_pair(unsigned::<u32>, char('|'), unsigned::<u32>));
+        let updates = || lines(comma_separated(unsigned::<u32>));
+        let input = "47|53\n97|13\n\n75,47\n97,61,53\n";
+        assert_eq!(
+            parse_all(input, separated_pair(rules(), blank_line, updates())),
+            Ok((
+                vec![(47, 53), (97, 13)],
+                vec![vec![75, 47], vec![97, 61, 53]]
+            ))
+        );
+        assert_eq!(
+            parse_all(
+                "1 2\n3\n\n4\n\n",
+                blocks(lines(space_separated(unsigned::<u8>)))
+            ),
+            Ok(vec![vec![vec![1, 2], vec![3]], vec![vec![4]]])
+        );
+    }
+
+    #[test]
+    fn reports_error_position() {
+        let err = parse_all("1 2\n3 x 4\n", lines(space_separated(unsigned::<u32>)));
+        assert_eq!(
+            err,
+            Err(ParseError {
+                line: 2,
+                column: 3,
+                kind: ErrorKind::Eof,
+                found: "x 4".to_string(),
+            })
+        );
+
+        let err = parse_all("1 2\nx\n", lines(space_separated(unsigned::<u32>))).unwrap_err();
+        assert_eq!((err.line, err.column, err.kind), (2, 1, ErrorKind::Digit));
+        assert_eq!(
+            err.to_string(),
+            "parse error at line 2, column 1: Digit failed on \"x\""
+        );
+
+        let err = parse_all("1\n\n2\n\nx", blocks(unsigned::<u32>)).unwrap_err();
+        assert_eq!((err.line, err.column), (5, 1));
+    }
+}
@@ -0,0 +1,285 @@
+use crate::math::gcd;
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// An exact fraction `numer / denom` over `i128`, always kept in lowest terms with a positive
+/// denominator, so equal values compare and hash equal.
+///
+/// Arithmetic cancels common factors before multiplying, but panics like integer arithmetic if an
+/// intermediate result still overflows.
+///
+/// # Display
+/// A rational displays as `numer/denom`, or just `numer` if it is an integer.
+///
+/// ```
+/// # use advent_of_code::rational::Rational;
+/// let third = Rational::new(2, 6);
+/// assert_eq!((third + Rational::from(1i64)).to_string(), "4/3");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numer: i128,
+    denom: i128,
+}
+
+impl Rational {
+    pub const ZERO: Self = Self::integer(0);
+    pub const ONE: Self = Self::integer(1);
+
+    /// Creates the fraction `numer / denom` in lowest terms.
+    ///
+    /// # Panics
+    /// Panics if `denom` is 0.
+    pub fn new(numer: i128, denom: i128) -> Self {
+        assert!(denom != 0, "denominator of a rational must not be 0");
+        let g = gcd(numer, denom) * denom.signum();
+        Self {
+            numer: numer / g,
+            denom: denom / g,
+        }
+    }
+
+    pub const fn integer(n: i128) -> Self {
+        Self { numer: n, denom: 1 }
+    }
+
+    pub const fn numer(self) -> i128 {
+        self.numer
+    }
+
+    /// The denominator, always positive.
+    pub const fn denom(self) -> i128 {
+        self.denom
+    }
+
+    pub const fn is_zero(self) -> bool {
+        self.numer == 0
+    }
+
+    pub const fn is_integer(self) -> bool {
+        self.denom == 1
+    }
+
+    /// The value as an integer, or [`None`] if it has a fractional part.
+    pub const fn to_integer(self) -> Option<i128> {
+        if self.is_integer() {
+            Some(self.numer)
+        } else {
+            None
+        }
+    }
+
+    /// The largest integer less than or equal to the value.
+    pub const fn floor(self) -> i128 {
+        self.numer.div_euclid(self.denom)
+    }
+
+    /// The smallest integer greater than or equal to the value.
+    pub const fn ceil(self) -> i128 {
+        -(-self.numer).div_euclid(self.denom)
+    }
+
+    pub const fn signum(self) -> i128 {
+        self.numer.signum()
+    }
+
+    /// The multiplicative inverse, or [`None`] for 0.
+    pub fn recip(self) -> Option<Self> {
+        (!self.is_zero()).then(|| Self::new(self.denom, self.numer))
+    }
+}
+
+impl Default for Rational {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(n: i128) -> Self {
+        Self::integer(n)
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Self {
+        Self::integer(n.into())
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let g = gcd(self.denom, other.denom);
+        Self::new(
+            self.numer * (other.denom / g) + other.numer * (self.denom / g),
+            self.denom / g * other.denom,
+        )
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        // cross-cancel first, so the products stay as small as possible.
+        let g1 = gcd(self.numer, other.denom);
+        let g2 = gcd(other.numer, self.denom);
+        Self::new(
+            (self.numer / g1) * (other.numer / g2),
+            (self.denom / g2) * (other.denom / g1),
+        )
+    }
+}
+
+impl Div for Rational {
+    type Output = Self;
+
+    /// # Panics
+    /// Panics when dividing by 0.
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, other: Self) -> Self {
+        self * other.recip().expect("division of a rational by 0")
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            numer: -self.numer,
+            denom: self.denom,
+        }
+    }
+}
+
+impl AddAssign for Rational {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Rational {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign for Rational {
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
+
+impl Ord for Rational {
+    /// Cross-multiplies if that fits, otherwise compares the continued fractions term by term,
+    /// which only divides and so cannot overflow.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (mut a, mut b) = (self.numer, self.denom);
+        let (mut c, mut d) = (other.numer, other.denom);
+        if let (Some(lhs), Some(rhs)) = (a.checked_mul(d), c.checked_mul(b)) {
+            return lhs.cmp(&rhs);
+        }
+
+        // a/b against c/d with positive denominators, flipped for each reciprocal taken.
+        let mut flipped = false;
+        let ordering = loop {
+            let ordering = a.div_euclid(b).cmp(&c.div_euclid(d));
+            if ordering != Ordering::Equal {
+                break ordering;
+            }
+            let (r1, r2) = (a.rem_euclid(b), c.rem_euclid(d));
+            if r1 == 0 || r2 == 0 {
+                break r1.cmp(&r2);
+            }
+            // r1/b against r2/d orders like d/r2 against b/r1.
+            (a, b, c, d) = (b, r1, d, r2);
+            flipped = !flipped;
+        };
+        if flipped {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::Rational;
+    use std::cmp::Ordering;
+
+    fn r(numer: i128, denom: i128) -> Rational {
+        Rational::new(numer, denom)
+    }
+
+    #[test]
+    fn normalizes() {
+        assert_eq!(r(2, 4), r(1, 2));
+        assert_eq!(r(1, -2), r(-1, 2));
+        assert_eq!(r(1, -2).denom(), 2);
+        assert_eq!(r(0, -5), Rational::ZERO);
+        assert_eq!(r(6, 3).to_integer(), Some(2));
+        assert_eq!(r(7, 3).to_integer(), None);
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(r(1, 2) + r(1, 3), r(5, 6));
+        assert_eq!(r(1, 2) - r(1, 3), r(1, 6));
+        assert_eq!(r(2, 3) * r(9, 4), r(3, 2));
+        assert_eq!(r(2, 3) / r(4, 9), r(3, 2));
+        assert_eq!(-r(2, 3), r(-2, 3));
+        assert_eq!(r(-2, 3).recip(), Some(r(-3, 2)));
+        assert_eq!(Rational::ZERO.recip(), None);
+        // cross-cancelling keeps large but representable products from overflowing
+        let big = r(i128::MAX, 3);
+        assert_eq!(big * r(3, i128::MAX), Rational::ONE);
+    }
+
+    #[test]
+    fn rounding_and_ordering() {
+        assert_eq!((r(7, 2).floor(), r(7, 2).ceil()), (3, 4));
+        assert_eq!((r(-7, 2).floor(), r(-7, 2).ceil()), (-4, -3));
+        assert_eq!((r(4, 2).floor(), r(4, 2).ceil()), (2, 2));
+        assert!(r(1, 3) < r(1, 2));
+        assert!(r(-1, 2) < r(-1, 3));
+        // cross-multiplying these overflows
+        assert!(r(i128::MAX, 7) > r(i128::MAX - 1, 7));
+        assert!(r(i128::MAX - 2, i128::MAX - 1) < r(i128::MAX - 1, i128::MAX));
+        assert!(r(i128::MIN + 1, 5) < r(i128::MIN + 2, 5));
+        assert!(r(i128::MIN + 1, i128::MAX - 1) < r(-1, 1));
+        assert_eq!(r(i128::MAX, 5).cmp(&r(i128::MAX, 5)), Ordering::Equal);
+        assert_eq!(r(5, 10).to_string(), "1/2");
+        assert_eq!(r(-4, 2).to_string(), "-2");
+    }
+}
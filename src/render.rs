@@ -0,0 +1,453 @@
+use crate::grid::Grid;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+const ANSI_RESET: &str = "\x1b[0m";
+/// Clears the terminal and moves the cursor to the top left corner.
+pub const ANSI_CLEAR: &str = "\x1b[2J\x1b[H";
+
+/// A 24-bit colour, used both for the terminal and for exported images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Self = Self(0, 0, 0);
+    pub const WHITE: Self = Self(255, 255, 255);
+    pub const GREY: Self = Self(128, 128, 128);
+    pub const RED: Self = Self(220, 50, 47);
+    pub const GREEN: Self = Self(133, 153, 0);
+    pub const BLUE: Self = Self(38, 139, 210);
+    pub const YELLOW: Self = Self(181, 137, 0);
+
+    /// The `index`-th of a sequence of colours where neighbours in the sequence are easy to tell
+    /// apart, e.g. to colour regions.
+    pub fn distinct(index: usize) -> Self {
+        // step around the hue circle by the golden angle, so nearby indices get far apart hues.
+        #[allow(clippy::cast_precision_loss)]
+        let hue = (index as f64 * 137.507_764) % 360.0;
+        let sector = hue / 60.0;
+        let x = 1.0 - (sector % 2.0 - 1.0).abs();
+        let (r, g, b) = match sector as u8 {
+            0 => (1.0, x, 0.0),
+            1 => (x, 1.0, 0.0),
+            2 => (0.0, 1.0, x),
+            3 => (0.0, x, 1.0),
+            4 => (x, 0.0, 1.0),
+            _ => (1.0, 0.0, x),
+        };
+        // keep the colours bright enough to read on a dark terminal.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let channel = |c: f64| (64.0 + c * 191.0) as u8;
+        Self(channel(r), channel(g), channel(b))
+    }
+}
+
+/// How a single cell is drawn in the terminal: a symbol with optional fore- and background colours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub symbol: char,
+    pub fg: Option<Rgb>,
+    pub bg: Option<Rgb>,
+}
+
+impl Tile {
+    pub const fn new(symbol: char) -> Self {
+        Self {
+            symbol,
+            fg: None,
+            bg: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn fg(self, color: Rgb) -> Self {
+        Self {
+            fg: Some(color),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub const fn bg(self, color: Rgb) -> Self {
+        Self {
+            bg: Some(color),
+            ..self
+        }
+    }
+}
+
+impl From<char> for Tile {
+    fn from(symbol: char) -> Self {
+        Self::new(symbol)
+    }
+}
+
+/// Draws the grid with one [`Tile`] per cell, as text with ANSI colour codes.
+///
+/// `tile` gets the position of each cell too, so overlays such as a path can be highlighted
+/// without copying the grid.
+pub fn render<T>(grid: &Grid<T>, mut tile: impl FnMut((usize, usize), &T) -> Tile) -> String {
+    let mut out = String::new();
+    for (pos, cell) in grid.iter() {
+        if pos.1 == 0 && pos.0 > 0 {
+            out.push('\n');
+        }
+        let Tile { symbol, fg, bg } = tile(pos, cell);
+        if let Some(Rgb(r, g, b)) = fg {
+            let _ = write!(out, "\x1b[38;2;{r};{g};{b}m");
+        }
+        if let Some(Rgb(r, g, b)) = bg {
+            let _ = write!(out, "\x1b[48;2;{r};{g};{b}m");
+        }
+        out.push(symbol);
+        if fg.is_some() || bg.is_some() {
+            out.push_str(ANSI_RESET);
+        }
+    }
+    out
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Keyboard commands for an interactive [`Player`], read line by line from stdin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Control {
+    /// An empty line.
+    TogglePause,
+    /// `n`, shows the next frame while paused.
+    Step,
+    /// `+`, halves the delay.
+    Faster,
+    /// `-`, doubles the delay.
+    Slower,
+    /// `q`
+    Quit,
+}
+
+impl Control {
+    fn parse(line: &str) -> Option<Self> {
+        match line.trim() {
+            "" => Some(Self::TogglePause),
+            "n" => Some(Self::Step),
+            "+" => Some(Self::Faster),
+            "-" => Some(Self::Slower),
+            "q" => Some(Self::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// Plays a sequence of frames in the terminal, redrawing the screen for every frame.
+///
+/// An interactive player can be paused and resumed with enter, stepped frame by frame with
+/// `n` + enter, sped up or slowed down with `+` / `-` and stopped with `q`.
+///
+/// ```no_run
+/// # use advent_of_code::render::Player;
+/// # use std::time::Duration;
+/// let frames = (0..10).map(|n| "#".repeat(n));
+/// Player::new(Duration::from_millis(100)).interactive().play(frames);
+/// ```
+pub struct Player {
+    delay: Duration,
+    paused: bool,
+    controls: Option<Receiver<Control>>,
+}
+
+impl Player {
+    /// A non-interactive player showing each frame for `delay`.
+    pub const fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            paused: false,
+            controls: None,
+        }
+    }
+
+    /// A player showing `fps` frames per second.
+    pub fn with_fps(fps: f64) -> Self {
+        Self::new(Duration::from_secs_f64(1.0 / fps.max(0.001)))
+    }
+
+    /// Reads controls from stdin while playing.
+    #[must_use]
+    pub fn interactive(self) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                let Ok(line) = line else { break };
+                if let Some(control) = Control::parse(&line) {
+                    if sender.send(control).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        self.with_controls(receiver)
+    }
+
+    fn with_controls(mut self, controls: Receiver<Control>) -> Self {
+        self.controls = Some(controls);
+        self
+    }
+
+    /// Starts paused, so the first frame stays on screen until resumed or stepped.
+    #[must_use]
+    pub const fn paused(mut self) -> Self {
+        self.paused = true;
+        self
+    }
+
+    /// Plays the frames on stdout. Frames are drawn as they are produced, so `frames` can run a
+    /// simulation lazily.
+    pub fn play(&mut self, frames: impl IntoIterator<Item = String>) {
+        let stdout = io::stdout();
+        let _ = self.play_to(&mut stdout.lock(), frames);
+    }
+
+    /// Plays the frames on `out`, returns the number of frames shown.
+    fn play_to(
+        &mut self,
+        out: &mut impl Write,
+        frames: impl IntoIterator<Item = String>,
+    ) -> io::Result<usize> {
+        let mut shown = 0;
+        for frame in frames {
+            shown += 1;
+            write!(out, "{ANSI_CLEAR}{frame}\n{}\n", self.status(shown))?;
+            out.flush()?;
+
+            if !self.wait()? {
+                break;
+            }
+        }
+        Ok(shown)
+    }
+
+    fn status(&self, frame: usize) -> String {
+        let mut status = format!("frame {frame} · {:.0?} per frame", self.delay);
+        if self.paused {
+            status.push_str(" · paused");
+        }
+        if self.controls.is_some() {
+            status.push_str(" · [enter] pause/resume, [n] step, [+/-] speed, [q] quit");
+        }
+        status
+    }
+
+    /// Waits until the next frame is due. Returns `false` if playback was stopped.
+    fn wait(&mut self) -> io::Result<bool> {
+        let Some(controls) = &self.controls else {
+            std::thread::sleep(self.delay);
+            return Ok(true);
+        };
+
+        loop {
+            let control = if self.paused {
+                controls.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            } else {
+                controls.recv_timeout(self.delay)
+            };
+            match control {
+                Ok(Control::TogglePause) => {
+                    self.paused = !self.paused;
+                    if !self.paused {
+                        return Ok(true);
+                    }
+                }
+                Ok(Control::Step) => {
+                    self.paused = true;
+                    return Ok(true);
+                }
+                Ok(Control::Faster) => self.delay /= 2,
+                Ok(Control::Slower) => self.delay = self.delay.saturating_mul(2),
+                Ok(Control::Quit) => return Ok(false),
+                Err(RecvTimeoutError::Timeout) => return Ok(true),
+                // stdin was closed, keep playing without controls.
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.controls = None;
+                    self.paused = false;
+                    return Ok(true);
+                }
+            }
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Writes the grid as a binary PPM (P6) image, each cell drawn as a `scale` x `scale` square.
+///
+/// # Errors
+/// Returns any error from writing to `out`.
+pub fn write_ppm<T>(
+    grid: &Grid<T>,
+    scale: usize,
+    mut color: impl FnMut(&T) -> Rgb,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let scale = scale.max(1);
+    writeln!(
+        out,
+        "P6\n{} {}\n255",
+        grid.cols() * scale,
+        grid.rows() * scale
+    )?;
+    for row in grid.iter_rows() {
+        let line = row
+            .iter()
+            .flat_map(|cell| {
+                let Rgb(r, g, b) = color(cell);
+                [r, g, b].repeat(scale)
+            })
+            .collect::<Vec<_>>();
+        for _ in 0..scale {
+            out.write_all(&line)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the grid as a binary PBM (P4) bitmap where cells for which `is_set` holds are black,
+/// each cell drawn as a `scale` x `scale` square.
+///
+/// # Errors
+/// Returns any error from writing to `out`.
+pub fn write_pbm<T>(
+    grid: &Grid<T>,
+    scale: usize,
+    mut is_set: impl FnMut(&T) -> bool,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let scale = scale.max(1);
+    writeln!(out, "P4\n{} {}", grid.cols() * scale, grid.rows() * scale)?;
+    for row in grid.iter_rows() {
+        let bits = row
+            .iter()
+            .flat_map(|cell| std::iter::repeat_n(is_set(cell), scale))
+            .collect::<Vec<_>>();
+        // rows are packed 8 pixels per byte, most significant bit first, padded to a full byte.
+        let line = bits
+            .chunks(8)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0u8, |byte, (idx, &bit)| byte | (u8::from(bit) << (7 - idx)))
+            })
+            .collect::<Vec<_>>();
+        for _ in 0..scale {
+            out.write_all(&line)?;
+        }
+    }
+    Ok(())
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{render, write_pbm, write_ppm, Control, Player, Rgb, Tile};
+    use crate::grid::Grid;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    fn grid() -> Grid<char> {
+        "#.\n.#".parse().unwrap()
+    }
+
+    #[test]
+    fn renders_tiles() {
+        let plain = render(&grid(), |_, &c| c.into());
+        assert_eq!(plain, "#.\n.#");
+
+        let coloured = render(&grid(), |pos, &c| {
+            if pos == (0, 0) {
+                Tile::new('@').fg(Rgb::RED).bg(Rgb::BLACK)
+            } else {
+                c.into()
+            }
+        });
+        assert_eq!(
+            coloured,
+            "\x1b[38;2;220;50;47m\x1b[48;2;0;0;0m@\x1b[0m.\n.#"
+        );
+    }
+
+    #[test]
+    fn distinct_colours() {
+        let colours = (0..6).map(Rgb::distinct).collect::<Vec<_>>();
+        for (idx, colour) in colours.iter().enumerate() {
+            assert!(!colours[idx + 1..].contains(colour));
+        }
+    }
+
+    #[test]
+    fn exports_ppm() {
+        let mut out = Vec::new();
+        write_ppm(
+            &grid(),
+            1,
+            |&c| if c == '#' { Rgb::BLACK } else { Rgb::WHITE },
+            &mut out,
+        )
+        .unwrap();
+        let mut expected = b"P6\n2 2\n255\n".to_vec();
+        expected.extend([0, 0, 0, 255, 255, 255, 255, 255, 255, 0, 0, 0]);
+        assert_eq!(out, expected);
+
+        let mut out = Vec::new();
+        write_ppm(&grid(), 3, |_| Rgb::GREY, &mut out).unwrap();
+        assert!(out.starts_with(b"P6\n6 6\n255\n"));
+        assert_eq!(out.len(), 11 + 6 * 6 * 3);
+    }
+
+    #[test]
+    fn exports_pbm() {
+        let mut out = Vec::new();
+        write_pbm(&grid(), 1, |&c| c == '#', &mut out).unwrap();
+        assert_eq!(out, b"P4\n2 2\n\x80\x40");
+
+        // 10 pixels per row need 2 bytes
+        let mut out = Vec::new();
+        write_pbm(&grid(), 5, |&c| c == '#', &mut out).unwrap();
+        assert_eq!(&out[..8], b"P4\n10 10");
+        assert_eq!(&out[9..11], [0b1111_1000, 0]);
+        assert_eq!(out.len(), 9 + 10 * 2);
+    }
+
+    #[test]
+    fn plays_with_controls() {
+        let (sender, receiver) = mpsc::channel();
+        // step twice through the paused player, then quit before the last frame.
+        for control in [Control::Step, Control::Faster, Control::Step, Control::Quit] {
+            sender.send(control).unwrap();
+        }
+        let mut player = Player::new(Duration::from_secs(60))
+            .paused()
+            .with_controls(receiver);
+        let mut out = Vec::new();
+        let frames = ["a", "b", "c", "d"].map(String::from);
+        assert_eq!(player.play_to(&mut out, frames).unwrap(), 3);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("\x1b[2J\x1b[Hc\nframe 3 · 30s per frame · paused"));
+        assert!(!out.contains("\x1b[Hd"));
+        assert_eq!(Control::parse(" n "), Some(Control::Step));
+        assert_eq!(Control::parse(""), Some(Control::TogglePause));
+    }
+
+    #[test]
+    fn slows_down_without_overflow() {
+        let (sender, receiver) = mpsc::channel();
+        for control in [Control::Slower, Control::Slower, Control::Step] {
+            sender.send(control).unwrap();
+        }
+        let mut player = Player::new(Duration::MAX / 3)
+            .paused()
+            .with_controls(receiver);
+        assert!(player.wait().unwrap());
+        assert_eq!(player.delay, Duration::MAX);
+    }
+}
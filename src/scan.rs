@@ -0,0 +1,195 @@
+//! Finding instructions like `mul(2,4)` in corrupted memory.
+//!
+//! An instruction is a registered name followed by its arguments in parentheses, separated by
+//! commas. Each argument is a run of up to [`Scanner::max_digits`] ASCII digits, optionally
+//! preceded by whitespace. Anything else in memory is noise.
+
+/// An instruction found in memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction<'a> {
+    /// The registered name.
+    pub name: &'a str,
+    pub args: Vec<u32>,
+    /// The byte offset of the instruction's first character.
+    pub offset: usize,
+    /// The instruction as it appears in memory, e.g. `mul(2,4)`.
+    pub text: &'a str,
+}
+
+/// Finds the instructions of a set of names and arities in one pass over the memory.
+#[derive(Debug, Clone)]
+pub struct Scanner {
+    /// The names and numbers of arguments, longest name first.
+    instructions: Vec<(String, usize)>,
+    max_digits: usize,
+}
+
+impl Default for Scanner {
+    fn default() -> Self {
+        Self {
+            instructions: Vec::new(),
+            max_digits: 3,
+        }
+    }
+}
+
+impl Scanner {
+    /// A scanner without any instructions, for arguments of up to 3 digits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an instruction taking `arity` arguments. A name registered again gets the new
+    /// arity.
+    pub fn instruction(mut self, name: &str, arity: usize) -> Self {
+        self.instructions.retain(|(existing, _)| existing != name);
+        self.instructions.push((name.to_string(), arity));
+        // a name that ends in another name has to be tried first.
+        self.instructions
+            .sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+        self
+    }
+
+    /// Sets the maximum number of digits of an argument.
+    pub const fn max_digits(mut self, max_digits: usize) -> Self {
+        self.max_digits = max_digits;
+        self
+    }
+
+    /// The instructions in `memory`, in order.
+    ///
+    /// Only the opening parentheses are looked for. Each is checked once for a registered name
+    /// right before it and valid arguments after it, and instructions cannot contain another
+    /// opening parenthesis, so every byte is looked at a bounded number of times.
+    ///
+    /// ```
+    /// # use advent_of_code::scan::Scanner;
+    /// let scanner = Scanner::new().instruction("mul", 2).instruction("do", 0);
+    /// let found = scanner
+    ///     .scan("xmul(2,4)%do()mul[3,7]!mul(1234,5)")
+    ///     .map(|instruction| (instruction.offset, instruction.text))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(found, [(1, "mul(2,4)"), (10, "do()")]);
+    /// ```
+    pub fn scan<'a>(&'a self, memory: &'a str) -> impl Iterator<Item = Instruction<'a>> + 'a {
+        memory
+            .match_indices('(')
+            .filter_map(|(open, _)| self.instruction_at(memory, open))
+    }
+
+    /// The instruction whose argument list opens at `open`, if any.
+    fn instruction_at<'a>(&self, memory: &'a str, open: usize) -> Option<Instruction<'a>> {
+        let before = &memory[..open];
+        self.instructions
+            .iter()
+            .filter(|(name, _)| before.ends_with(name.as_str()))
+            .find_map(|(name, arity)| {
+                let (args, len) = self.arguments(&memory[open + 1..], *arity)?;
+                let offset = open - name.len();
+                Some(Instruction {
+                    name: &memory[offset..open],
+                    args,
+                    offset,
+                    text: &memory[offset..=open + len],
+                })
+            })
+    }
+
+    /// Parses `arity` arguments and the closing parenthesis at the start of `rest`. Returns the
+    /// arguments and the length of the text they take up, the parenthesis included.
+    fn arguments(&self, rest: &str, arity: usize) -> Option<(Vec<u32>, usize)> {
+        let mut args = Vec::with_capacity(arity);
+        let mut remaining = rest;
+        for idx in 0..arity {
+            if idx > 0 {
+                remaining = remaining.strip_prefix(',')?;
+            }
+            remaining = remaining.trim_start_matches(|c: char| c.is_ascii_whitespace());
+            let digits = remaining.bytes().take_while(u8::is_ascii_digit).count();
+            if digits == 0 || digits > self.max_digits {
+                return None;
+            }
+            args.push(remaining[..digits].parse().ok()?);
+            remaining = &remaining[digits..];
+        }
+        remaining = remaining.strip_prefix(')')?;
+
+        Some((args, rest.len() - remaining.len()))
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{Instruction, Scanner};
+
+    #[test]
+    fn finds_instructions_in_noise() {
+        let scanner = Scanner::new()
+            .instruction("mul", 2)
+            .instruction("do", 0)
+            .instruction("don't", 0);
+        let memory = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        let found = scanner
+            .scan(memory)
+            .map(|instruction| (instruction.name, instruction.args))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [
+                ("mul", vec![2, 4]),
+                ("don't", vec![]),
+                ("mul", vec![5, 5]),
+                ("mul", vec![11, 8]),
+                ("do", vec![]),
+                ("mul", vec![8, 5]),
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_arguments() {
+        let scanner = Scanner::new().instruction("mul", 2);
+        let found = |memory| scanner.scan(memory).count();
+        assert_eq!(found("mul(1,2,3)"), 0);
+        assert_eq!(found("mul(1)"), 0);
+        assert_eq!(found("mul(,1)"), 0);
+        assert_eq!(found("mul(1000,1)"), 0);
+        assert_eq!(found("mul (1,2)"), 0);
+        assert_eq!(found("mul( 1,\n2)"), 1);
+        assert_eq!(
+            Scanner::new()
+                .instruction("mul", 2)
+                .max_digits(4)
+                .scan("mul(1000,1)")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn prefers_longest_name() {
+        let scanner = Scanner::new()
+            .instruction("set", 1)
+            .instruction("reset", 0)
+            .instruction("set", 0);
+        assert_eq!(
+            scanner.scan("unreset()set()").collect::<Vec<_>>(),
+            [
+                Instruction {
+                    name: "reset",
+                    args: vec![],
+                    offset: 2,
+                    text: "reset()"
+                },
+                Instruction {
+                    name: "set",
+                    args: vec![],
+                    offset: 9,
+                    text: "set()"
+                },
+            ]
+        );
+    }
+}
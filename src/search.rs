@@ -0,0 +1,405 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// The outcome of a search from one or more start states.
+///
+/// Besides the minimum cost of every reached state, it keeps *all* predecessors that reach a state
+/// at its minimum cost. Together they form a DAG of every optimal path, which can be used to count
+/// optimal paths or to collect the states lying on any of them.
+#[derive(Debug, Clone)]
+pub struct Search<S, C> {
+    /// Minimum cost to reach each explored state.
+    pub costs: HashMap<S, C>,
+    /// All predecessors that reach a state at its minimum cost. Start states have none.
+    pub parents: HashMap<S, Vec<S>>,
+    /// The goal states reached at the minimum goal cost, in the order they were found.
+    pub goals: Vec<S>,
+}
+
+impl<S: Clone + Eq + Hash, C: Copy> Search<S, C> {
+    fn new() -> Self {
+        Self {
+            costs: HashMap::new(),
+            parents: HashMap::new(),
+            goals: Vec::new(),
+        }
+    }
+
+    /// The minimum cost to reach `state`, or [`None`] if it was not reached.
+    pub fn cost(&self, state: &S) -> Option<C> {
+        self.costs.get(state).copied()
+    }
+
+    /// The minimum cost to reach a goal, or [`None`] if no goal was reached.
+    pub fn goal_cost(&self) -> Option<C> {
+        self.goals.first().and_then(|goal| self.cost(goal))
+    }
+
+    /// One optimal path from a start state to `target`, both included.
+    pub fn path(&self, target: &S) -> Option<Vec<S>> {
+        self.costs.get(target)?;
+        let mut path = vec![target.clone()];
+        while let Some(parent) = self
+            .parents
+            .get(path.last()?)
+            .and_then(|parents| parents.first())
+        {
+            path.push(parent.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// The number of distinct optimal paths from any start state to `target`.
+    pub fn count_paths(&self, target: &S) -> u64 {
+        if !self.costs.contains_key(target) {
+            return 0;
+        }
+
+        // iterative post-order walk over the predecessor DAG, so long paths do not overflow the stack.
+        let mut counts: HashMap<&S, u64> = HashMap::new();
+        let mut stack = vec![(target, false)];
+        while let Some((state, expanded)) = stack.pop() {
+            if counts.contains_key(state) {
+                continue;
+            }
+            let parents = self.parents.get(state).map_or(&[][..], Vec::as_slice);
+            if parents.is_empty() {
+                counts.insert(state, 1);
+            } else if expanded {
+                let count = parents.iter().map(|parent| counts[parent]).sum();
+                counts.insert(state, count);
+            } else {
+                stack.push((state, true));
+                stack.extend(parents.iter().map(|parent| (parent, false)));
+            }
+        }
+
+        counts[target]
+    }
+
+    /// All states that lie on at least one optimal path to any of the `targets`, targets included.
+    pub fn states_on_paths<'a>(&'a self, targets: impl IntoIterator<Item = &'a S>) -> HashSet<S> {
+        let mut seen = HashSet::new();
+        let mut queue = targets
+            .into_iter()
+            .filter(|target| self.costs.contains_key(target))
+            .collect::<Vec<_>>();
+        while let Some(state) = queue.pop() {
+            if !seen.insert(state.clone()) {
+                continue;
+            }
+            if let Some(parents) = self.parents.get(state) {
+                queue.extend(parents);
+            }
+        }
+
+        seen
+    }
+
+    /// Records an edge from `from` to `to` reaching `to` at `cost`.
+    /// Returns `true` if the cost of `to` improved and it needs to be (re-)visited.
+    ///
+    /// Parents form a DAG as long as every parent has a lower cost. A zero-cost step does not
+    /// lower it, so it only adds a parent to a state that is not `settled` yet, i.e. has not been
+    /// expanded. States are settled in order, which rules out cycles of zero-cost parents.
+    fn relax(&mut self, from: &S, to: S, cost: C, settled: &HashSet<S>) -> bool
+    where
+        C: Ord,
+    {
+        match self.costs.get(&to).map(|&known| cost.cmp(&known)) {
+            Some(Ordering::Greater) => false,
+            Some(Ordering::Equal) => {
+                let zero_step = self.costs.get(from) == Some(&cost);
+                if zero_step && settled.contains(&to) {
+                    return false;
+                }
+                // start states are the only ones reached without a parent.
+                if let Some(parents) = self.parents.get_mut(&to) {
+                    if !parents.contains(from) {
+                        parents.push(from.clone());
+                    }
+                }
+                false
+            }
+            Some(Ordering::Less) | None => {
+                self.costs.insert(to.clone(), cost);
+                self.parents.insert(to, vec![from.clone()]);
+                true
+            }
+        }
+    }
+}
+
+/// Breadth-first search where every step costs 1.
+///
+/// Stops once all goal states at the minimum distance have been found. If `is_goal` never matches,
+/// every reachable state is explored.
+pub fn bfs<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut neighbours: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Search<S, usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut search = Search::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if !search.costs.contains_key(&start) {
+            search.costs.insert(start.clone(), 0);
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(state) = queue.pop_front() {
+        let cost = search.costs[&state];
+        if search.goal_cost().is_some_and(|best| cost > best) {
+            break;
+        }
+        if is_goal(&state) {
+            search.goals.push(state);
+            continue;
+        }
+        for next in neighbours(&state) {
+            // every step costs 1, so no parent is ever reached by a zero-cost step.
+            if search.relax(&state, next.clone(), cost + 1, &HashSet::new()) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    search
+}
+
+/// Dijkstra's algorithm over `neighbours`, which yields each successor state with the cost of the step.
+///
+/// Step costs must not be negative. Stops once all goal states at the minimum cost have been found.
+/// If `is_goal` never matches, every reachable state is explored.
+pub fn dijkstra<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    neighbours: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Search<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(starts, neighbours, |_| C::default(), is_goal)
+}
+
+/// A* search over `neighbours`, guided by `heuristic`.
+///
+/// The heuristic must be consistent (never overestimate the cost of a step) for the results,
+/// including the tracked predecessors, to be optimal. With a zero heuristic this is [`dijkstra`].
+pub fn astar<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut neighbours: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Search<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut search = Search::new();
+    let mut settled = HashSet::new();
+    let mut queue = BinaryHeap::new();
+    for start in starts {
+        if !search.costs.contains_key(&start) {
+            search.costs.insert(start.clone(), C::default());
+            queue.push(Entry {
+                priority: heuristic(&start),
+                cost: C::default(),
+                state: start,
+            });
+        }
+    }
+
+    while let Some(Entry {
+        priority,
+        cost,
+        state,
+    }) = queue.pop()
+    {
+        if search.goal_cost().is_some_and(|best| priority > best) {
+            break;
+        }
+        // skip entries that were superseded by a cheaper path.
+        if search.costs.get(&state).is_some_and(|&known| cost > known) {
+            continue;
+        }
+        settled.insert(state.clone());
+        if is_goal(&state) {
+            if !search.goals.contains(&state) {
+                search.goals.push(state);
+            }
+            continue;
+        }
+        for (next, step) in neighbours(&state) {
+            let next_cost = cost + step;
+            if search.relax(&state, next.clone(), next_cost, &settled) {
+                queue.push(Entry {
+                    priority: next_cost + heuristic(&next),
+                    cost: next_cost,
+                    state: next,
+                });
+            }
+        }
+    }
+
+    search
+}
+
+/// A state in the priority queue, ordered so that the [`BinaryHeap`] pops the lowest priority first.
+struct Entry<S, C> {
+    priority: C,
+    cost: C,
+    state: S,
+}
+
+impl<S, C: Ord> Ord for Entry<S, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .cmp(&self.priority)
+            .then_with(|| self.cost.cmp(&other.cost))
+    }
+}
+
+impl<S, C: Ord> PartialOrd for Entry<S, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S, C: Ord> PartialEq for Entry<S, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<S, C: Ord> Eq for Entry<S, C> {}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{astar, bfs, dijkstra};
+    use crate::geometry::Point;
+    use crate::grid::Grid;
+
+    const MAZE: &str = "\
+S..#
+.#..
+...E";
+
+    fn maze() -> (Grid<char>, Point, Point) {
+        let grid: Grid<char> = MAZE.parse().unwrap();
+        let start = Point::from_index(grid.find(|&c| c == 'S').unwrap());
+        let end = Point::from_index(grid.find(|&c| c == 'E').unwrap());
+        (grid, start, end)
+    }
+
+    fn open_neighbours(grid: &Grid<char>, point: Point) -> Vec<Point> {
+        point
+            .neighbours4()
+            .into_iter()
+            .filter(|&next| grid.at(next).is_some_and(|&c| c != '#'))
+            .collect()
+    }
+
+    #[test]
+    fn bfs_finds_all_shortest_paths() {
+        let (grid, start, end) = maze();
+        let search = bfs([start], |&p| open_neighbours(&grid, p), |&p| p == end);
+        assert_eq!(search.goal_cost(), Some(5));
+        assert_eq!(search.goals, [end]);
+        // around the wall at (1, 1) on either side, or along the bottom row
+        assert_eq!(search.count_paths(&end), 3);
+        assert_eq!(search.path(&end).unwrap().len(), 6);
+        assert_eq!(search.states_on_paths([&end]).len(), 10);
+    }
+
+    #[test]
+    fn bfs_explores_everything_without_goal() {
+        let (grid, start, _) = maze();
+        let search = bfs([start], |&p| open_neighbours(&grid, p), |_| false);
+        assert!(search.goals.is_empty());
+        assert_eq!(search.costs.len(), 10);
+        assert_eq!(search.cost(&Point::new(0, 3)), None);
+    }
+
+    #[test]
+    fn dijkstra_tracks_equal_cost_parents() {
+        // diamond: 0 -> 1 -> 3 and 0 -> 2 -> 3 with equal cost, 0 -> 3 directly costs more.
+        let edges = |&s: &u8| -> Vec<(u8, u32)> {
+            match s {
+                0 => vec![(1, 2), (2, 1), (3, 5)],
+                1 => vec![(3, 1)],
+                2 => vec![(3, 2)],
+                _ => vec![],
+            }
+        };
+        let search = dijkstra([0], edges, |&s| s == 3);
+        assert_eq!(search.goal_cost(), Some(3));
+        assert_eq!(search.count_paths(&3), 2);
+        let mut parents = search.parents[&3].clone();
+        parents.sort_unstable();
+        assert_eq!(parents, [1, 2]);
+        assert_eq!(search.states_on_paths([&3]).len(), 4);
+    }
+
+    #[test]
+    fn astar_matches_dijkstra() {
+        let (grid, start, end) = maze();
+        let neighbours = |p: &Point| {
+            open_neighbours(&grid, *p)
+                .into_iter()
+                .map(|next| (next, 1u64))
+                .collect::<Vec<_>>()
+        };
+        let plain = dijkstra([start], neighbours, |&p| p == end);
+        let guided = astar([start], neighbours, |p| p.manhattan(end), |&p| p == end);
+        assert_eq!(plain.goal_cost(), guided.goal_cost());
+        assert_eq!(plain.count_paths(&end), guided.count_paths(&end));
+        assert_eq!(
+            plain.states_on_paths([&end]),
+            guided.states_on_paths([&end])
+        );
+    }
+
+    #[test]
+    fn zero_cost_cycles() {
+        // 0 and 1 lead to each other for free, 1 and 2 too, and 2 leads to the goal 3.
+        let edges = |&s: &u8| -> Vec<(u8, u32)> {
+            match s {
+                0 => vec![(1, 0)],
+                1 => vec![(0, 0), (2, 0)],
+                2 => vec![(1, 0), (3, 4)],
+                _ => vec![],
+            }
+        };
+        let search = dijkstra([0], edges, |&s| s == 3);
+        assert_eq!(search.goal_cost(), Some(4));
+        assert!(!search.parents.contains_key(&0));
+        assert_eq!(search.parents[&1], [0]);
+        assert_eq!(search.parents[&2], [1]);
+        assert_eq!(search.path(&3), Some(vec![0, 1, 2, 3]));
+        assert_eq!(search.count_paths(&3), 1);
+        assert_eq!(search.states_on_paths([&3]).len(), 4);
+    }
+
+    #[test]
+    fn unreachable_goal() {
+        let search = dijkstra([0u8], |_| Vec::<(u8, u32)>::new(), |&s| s == 1);
+        assert_eq!(search.goal_cost(), None);
+        assert_eq!(search.count_paths(&1), 0);
+        assert!(search.path(&1).is_none());
+    }
+}
@@ -1,8 +1,23 @@
-use std::process::{Command, Stdio};
+use std::process::{self, Command, Stdio};
 
-use crate::template::Day;
+use crate::template::{Day, Input};
+
+#[allow(clippy::too_many_arguments)]
+pub fn handle(
+    day: Day,
+    release: bool,
+    dhat: bool,
+    profile: bool,
+    submit_part: Option<u8>,
+    input: &Input,
+    part: Option<u8>,
+    extra: &[String],
+) {
+    if submit_part.is_some() && *input != Input::Puzzle {
+        eprintln!("Only results for the puzzle input can be submitted. Remove `--example` / `--input` to submit.");
+        process::exit(1);
+    }
 
-pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>) {
     let mut cmd_args = vec!["run".to_string(), "--bin".to_string(), day.to_string()];
 
     if dhat {
@@ -12,10 +27,14 @@ pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>) {
             "--features".to_string(),
             "dhat-heap".to_string(),
         ]);
-    } else if release {
+    } else if release || profile {
         cmd_args.push("--release".to_string());
     }
 
+    if profile {
+        cmd_args.extend(["--features".to_string(), "profile".to_string()]);
+    }
+
     cmd_args.push("--".to_string());
 
     if let Some(submit_part) = submit_part {
@@ -23,6 +42,14 @@ pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>) {
         cmd_args.push(submit_part.to_string());
     }
 
+    if let Some(part) = part {
+        cmd_args.push("--part".to_string());
+        cmd_args.push(part.to_string());
+    }
+
+    cmd_args.extend(input.to_args());
+    cmd_args.extend_from_slice(extra);
+
     let mut cmd = Command::new("cargo")
         .args(&cmd_args)
         .stdout(Stdio::inherit())
@@ -1,7 +1,10 @@
-use std::{env, fs};
+use std::io::{self, Read};
+use std::{env, fs, process};
 
 pub mod aoc_cli;
 pub mod commands;
+#[cfg(feature = "profile")]
+pub mod profile;
 pub mod runner;
 
 pub use day::*;
@@ -36,22 +39,137 @@ pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
     f.expect("could not open input file")
 }
 
+/// The input a solution is run against, as selected on the command line.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Input {
+    /// The puzzle input in `data/inputs`.
+    #[default]
+    Puzzle,
+    /// An example in `data/examples`, optionally with a part suffix. E.g. like `01-2.txt`.
+    Example(Option<u8>),
+    /// An arbitrary file, or stdin when the path is `-`.
+    Path(String),
+}
+
+impl Input {
+    /// Parses the `--example [k]` and `--input <path>` options.
+    pub fn parse(args: &mut pico_args::Arguments) -> Result<Self, pico_args::Error> {
+        let path: Option<String> = args.opt_value_from_str("--input")?;
+        // the example suffix is optional, so `--example` may be followed by another option.
+        let example = match args.opt_value_from_fn("--example", str::parse::<u8>) {
+            Ok(Some(k)) => Some(Some(k)),
+            Ok(None) => None,
+            Err(_) => args.contains("--example").then_some(None),
+        };
+
+        match (example, path) {
+            (Some(_), Some(_)) => Err(pico_args::Error::ArgumentParsingFailed {
+                cause: "`--example` and `--input` cannot be combined".into(),
+            }),
+            (Some(k), None) => Ok(Self::Example(k)),
+            (None, Some(path)) => Ok(Self::Path(path)),
+            (None, None) => Ok(Self::Puzzle),
+        }
+    }
+
+    /// Converts the input back into arguments that can be forwarded to a solution binary.
+    #[must_use]
+    pub fn to_args(&self) -> Vec<String> {
+        match self {
+            Self::Puzzle => vec![],
+            Self::Example(None) => vec!["--example".into()],
+            Self::Example(Some(k)) => vec!["--example".into(), k.to_string()],
+            Self::Path(path) => vec!["--input".into(), path.clone()],
+        }
+    }
+
+    /// Reads the selected input for a day to a string.
+    pub fn read(&self, day: Day) -> io::Result<String> {
+        let cwd = env::current_dir()?;
+        let filepath = match self {
+            Self::Puzzle => cwd.join("data").join("inputs").join(format!("{day}.txt")),
+            Self::Example(None) => cwd.join("data").join("examples").join(format!("{day}.txt")),
+            Self::Example(Some(k)) => cwd
+                .join("data")
+                .join("examples")
+                .join(format!("{day}-{k}.txt")),
+            Self::Path(path) if path == "-" => {
+                let mut input = String::new();
+                io::stdin().read_to_string(&mut input)?;
+                return Ok(input);
+            }
+            Self::Path(path) => path.into(),
+        };
+        fs::read_to_string(filepath)
+    }
+}
+
+/// Parses a part number passed via `--part` or `--submit`.
+pub fn parse_part(s: &str) -> Result<u8, &'static str> {
+    match s.parse() {
+        Ok(part @ (1 | 2)) => Ok(part),
+        _ => Err("expecting part 1 or 2"),
+    }
+}
+
+/// Reads the input selected with `--example [k]` or `--input <path>`, defaulting to the puzzle input.
+#[must_use]
+pub fn read_input(day: Day) -> String {
+    let input = Input::parse(&mut pico_args::Arguments::from_env()).unwrap_or_else(|e| {
+        eprintln!("Error: {e}");
+        process::exit(1);
+    });
+
+    input.read(day).unwrap_or_else(|e| {
+        eprintln!("could not open input file: {e}");
+        process::exit(1);
+    })
+}
+
+/// Returns the part selected with `--part`, if any.
+#[must_use]
+pub fn selected_part() -> Option<u8> {
+    pico_args::Arguments::from_env()
+        .opt_value_from_fn("--part", parse_part)
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            process::exit(1);
+        })
+}
+
+/// Whether `part` is the last part that runs, either the one selected with `--part` or part 2.
+///
+/// Lets a `show` function of [`solution!`](crate::solution) print things that concern the input
+/// as a whole only once, like why it could not be parsed.
+#[must_use]
+pub fn is_last_part(part: u8) -> bool {
+    selected_part().unwrap_or(2) == part
+}
+
 /// Creates the constant `DAY` and sets up the input and runner for each part.
 ///
 /// The optional, second parameter (1 or 2) allows you to only run a single part of the solution.
+/// A single part can also be selected at runtime with `--part`, the input with `--example [k]` or `--input <path>`.
+///
+/// With `show = <fn>`, the function is called as `show(&input, part)` after each part has run.
+/// It is not timed, so it is the place for output a solution only produces on request, like
+/// drawings or reports behind a command line flag, and for explaining why a part failed.
 #[macro_export]
 macro_rules! solution {
     ($day:expr) => {
-        $crate::solution!(@impl $day, [part_one, 1] [part_two, 2]);
+        $crate::solution!(@impl $day, |_, _| {}, [part_one, 1] [part_two, 2]);
     };
     ($day:expr, 1) => {
-        $crate::solution!(@impl $day, [part_one, 1]);
+        $crate::solution!(@impl $day, |_, _| {}, [part_one, 1]);
     };
     ($day:expr, 2) => {
-        $crate::solution!(@impl $day, [part_two, 2]);
+        $crate::solution!(@impl $day, |_, _| {}, [part_two, 2]);
+    };
+    ($day:expr, show = $show:expr) => {
+        $crate::solution!(@impl $day, $show, [part_one, 1] [part_two, 2]);
     };
 
-    (@impl $day:expr, $( [$func:expr, $part:expr] )*) => {
+    (@impl $day:expr, $show:expr, $( [$func:expr, $part:expr] )*) => {
         /// The current day.
         const DAY: $crate::template::Day = $crate::day!($day);
 
@@ -61,8 +179,83 @@ macro_rules! solution {
 
         fn main() {
             use $crate::template::runner::*;
-            let input = $crate::template::read_file("inputs", DAY);
-            $( run_part($func, &input, DAY, $part); )*
+            let input = $crate::template::read_input(DAY);
+            let selected = $crate::template::selected_part();
+            $(
+                if selected.is_none_or(|part| part == $part) {
+                    run_part($func, &input, DAY, $part);
+                    let show: fn(&str, u8) = $show;
+                    show(&input, $part);
+                }
+            )*
         }
     };
 }
+
+/// Marks the rest of the enclosing block as a named timing span.
+///
+/// Spans nest, and are only recorded when the `profile` feature is enabled (`cargo solve <day> --profile`).
+/// Otherwise the macro expands to nothing.
+///
+/// ```
+/// # fn parse_input(input: &str) -> Vec<u32> {
+/// #     input.lines().filter_map(|line| line.parse().ok()).collect()
+/// # }
+/// # let input = "1\n2\n3";
+/// let numbers = {
+///     advent_of_code::span!("parse");
+///     parse_input(input)
+/// };
+/// # assert_eq!(numbers, [1, 2, 3]);
+/// ```
+#[macro_export]
+macro_rules! span {
+    ($name:expr) => {
+        #[cfg(feature = "profile")]
+        let _span = $crate::template::profile::Span::enter($name);
+    };
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{parse_part, Input};
+
+    fn parse(args: &[&str]) -> Input {
+        let mut args = pico_args::Arguments::from_vec(args.iter().map(Into::into).collect());
+        Input::parse(&mut args).unwrap()
+    }
+
+    #[test]
+    fn parses_input_options() {
+        assert_eq!(parse(&[]), Input::Puzzle);
+        assert_eq!(parse(&["--example"]), Input::Example(None));
+        assert_eq!(parse(&["--example", "2"]), Input::Example(Some(2)));
+        assert_eq!(parse(&["--example", "--time"]), Input::Example(None));
+        assert_eq!(parse(&["--input", "-"]), Input::Path("-".into()));
+        assert_eq!(
+            parse(&["--input", "data/inputs/01.txt"]),
+            Input::Path("data/inputs/01.txt".into())
+        );
+    }
+
+    #[test]
+    fn forwards_input_options() {
+        for input in [
+            Input::Puzzle,
+            Input::Example(None),
+            Input::Example(Some(2)),
+            Input::Path("-".into()),
+        ] {
+            let args = input.to_args();
+            let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+            assert_eq!(parse(&args), input);
+        }
+    }
+
+    #[test]
+    fn parses_parts() {
+        assert_eq!(parse_part("1"), Ok(1));
+        assert_eq!(parse_part("2"), Ok(2));
+        assert!(parse_part("3").is_err());
+    }
+}
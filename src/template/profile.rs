@@ -0,0 +1,231 @@
+/// Hierarchical timing spans for solutions, enabled with the `profile` feature.
+///
+/// Solutions mark phases with [`span!`](crate::span). Completed spans are collected per part,
+/// printed as a tree of total / self time and exported as a Chrome trace-event file.
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs,
+    io::Error,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+use tinyjson::JsonValue;
+
+use crate::template::{Day, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+/// A completed span.
+#[derive(Clone, Debug)]
+struct Event {
+    /// Names of all enclosing spans, including this one.
+    path: Vec<&'static str>,
+    thread: u64,
+    start: Duration,
+    duration: Duration,
+}
+
+static EVENTS: Mutex<Vec<Event>> = Mutex::new(vec![]);
+static RECORDING: AtomicBool = AtomicBool::new(true);
+static NEXT_THREAD: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static STACK: RefCell<Vec<&'static str>> = const { RefCell::new(vec![]) };
+    static THREAD: u64 = NEXT_THREAD.fetch_add(1, Ordering::Relaxed);
+}
+
+fn epoch() -> Instant {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    *EPOCH.get_or_init(Instant::now)
+}
+
+/// Guard returned by [`span!`](crate::span), records the span when dropped.
+pub struct Span {
+    start: Instant,
+}
+
+impl Span {
+    #[must_use]
+    pub fn enter(name: &'static str) -> Self {
+        epoch();
+        STACK.with_borrow_mut(|stack| stack.push(name));
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let duration = self.start.elapsed();
+        let path = STACK.with_borrow_mut(|stack| {
+            let path = stack.clone();
+            stack.pop();
+            path
+        });
+
+        if RECORDING.load(Ordering::Relaxed) {
+            EVENTS.lock().unwrap().push(Event {
+                path,
+                thread: THREAD.with(|thread| *thread),
+                start: self.start.saturating_duration_since(epoch()),
+                duration,
+            });
+        }
+    }
+}
+
+/// Stops recording spans, e.g. while a part is being benched.
+pub fn pause() {
+    RECORDING.store(false, Ordering::Relaxed);
+}
+
+/// Prints the spans recorded for a part and writes them to `profile-<day>-<part>.json`.
+/// Clears the recorded spans and resumes recording afterwards.
+pub fn report(day: Day, part: u8) {
+    let events = std::mem::take(&mut *EVENTS.lock().unwrap());
+    RECORDING.store(true, Ordering::Relaxed);
+
+    if events.is_empty() {
+        return;
+    }
+
+    println!("{}", format_tree(&events));
+
+    let path = format!("profile-{day}-{part}.json");
+    match write_trace(&events, &path) {
+        Ok(()) => println!("{ANSI_ITALIC}trace written to \"{path}\"{ANSI_RESET}"),
+        Err(e) => eprintln!("failed to write trace: {e}"),
+    }
+}
+
+/// Aggregated timings for all spans that share the same path.
+#[derive(Default)]
+struct Node {
+    total: Duration,
+    calls: usize,
+    children: Vec<usize>,
+}
+
+fn format_tree(events: &[Event]) -> String {
+    let mut events = events.to_vec();
+    events.sort_by_key(|event| (event.start, event.path.len()));
+
+    // paths are numbered in order of first appearance, so the tree keeps execution order.
+    let mut index: HashMap<&[&'static str], usize> = HashMap::new();
+    let mut nodes: Vec<(&[&'static str], Node)> = vec![];
+    let mut roots = vec![];
+    for event in &events {
+        for depth in 1..=event.path.len() {
+            let path = &event.path[..depth];
+            if index.contains_key(path) {
+                continue;
+            }
+            let idx = nodes.len();
+            index.insert(path, idx);
+            nodes.push((path, Node::default()));
+            match index.get(&path[..depth - 1]) {
+                Some(&parent) if depth > 1 => nodes[parent].1.children.push(idx),
+                _ => roots.push(idx),
+            }
+        }
+        let node = &mut nodes[index[event.path.as_slice()]].1;
+        node.total += event.duration;
+        node.calls += 1;
+    }
+
+    let mut out = format!(
+        "{ANSI_BOLD}{:>12} {:>12} {:>8}  span{ANSI_RESET}",
+        "total", "self", "calls"
+    );
+    let mut stack = roots.into_iter().rev().collect::<Vec<_>>();
+    while let Some(idx) = stack.pop() {
+        let (path, node) = &nodes[idx];
+        let children_total = node
+            .children
+            .iter()
+            .map(|&child| nodes[child].1.total)
+            .sum::<Duration>();
+        let self_time = node.total.saturating_sub(children_total);
+        out.push_str(&format!(
+            "\n{:>12} {:>12} {:>8}  {}{}",
+            format!("{:.1?}", node.total),
+            format!("{self_time:.1?}"),
+            node.calls,
+            "  ".repeat(path.len() - 1),
+            path[path.len() - 1]
+        ));
+        stack.extend(node.children.iter().rev());
+    }
+
+    out
+}
+
+fn write_trace(events: &[Event], path: &str) -> Result<(), Error> {
+    let trace_events = events
+        .iter()
+        .map(|event| {
+            let mut map: HashMap<String, JsonValue> = HashMap::new();
+            map.insert(
+                "name".into(),
+                JsonValue::String(event.path[event.path.len() - 1].into()),
+            );
+            map.insert("ph".into(), JsonValue::String("X".into()));
+            map.insert("ts".into(), JsonValue::Number(micros(event.start)));
+            map.insert("dur".into(), JsonValue::Number(micros(event.duration)));
+            map.insert("pid".into(), JsonValue::Number(1.0));
+            #[allow(clippy::cast_precision_loss)]
+            map.insert("tid".into(), JsonValue::Number(event.thread as f64));
+            JsonValue::Object(map)
+        })
+        .collect();
+
+    let mut map: HashMap<String, JsonValue> = HashMap::new();
+    map.insert("traceEvents".into(), JsonValue::Array(trace_events));
+    let mut file = fs::File::create(path)?;
+    JsonValue::Object(map).format_to(&mut file)
+}
+
+fn micros(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1_000_000_f64
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{format_tree, Event};
+    use std::time::Duration;
+
+    fn event(path: &[&'static str], start: u64, duration: u64) -> Event {
+        Event {
+            path: path.to_vec(),
+            thread: 1,
+            start: Duration::from_millis(start),
+            duration: Duration::from_millis(duration),
+        }
+    }
+
+    #[test]
+    fn aggregates_spans_into_tree() {
+        let tree = format_tree(&[
+            event(&["parse"], 0, 2),
+            event(&["search", "step"], 3, 1),
+            event(&["search", "step"], 5, 2),
+            event(&["search"], 2, 8),
+        ]);
+        let lines = tree.lines().skip(1).collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("  parse"));
+        assert_eq!(
+            lines[1].split_whitespace().collect::<Vec<_>>(),
+            ["8.0ms", "5.0ms", "1", "search"]
+        );
+        assert!(lines[2].ends_with("    step"));
+        assert_eq!(
+            lines[2].split_whitespace().collect::<Vec<_>>(),
+            ["3.0ms", "3.0ms", "2", "step"]
+        );
+    }
+}
@@ -17,6 +17,9 @@ pub fn run_part<I: Copy, T: Display>(func: impl Fn(I) -> Option<T>, input: I, da
 
     print_result(&result, &part_str, &format_duration(&duration, samples));
 
+    #[cfg(feature = "profile")]
+    crate::template::profile::report(day, part);
+
     if let Some(result) = result {
         submit_result(result, day, part);
     }
@@ -39,6 +42,10 @@ fn run_timed<I: Copy, T>(
     };
     let base_time = timer.elapsed();
 
+    // only spans of the first run are reported, benching would skew them.
+    #[cfg(feature = "profile")]
+    crate::template::profile::pause();
+
     hook(&result);
 
     let run = if std::env::args().any(|x| x == "--time") {